@@ -81,12 +81,54 @@ pub struct Chart {
 #[serde(rename_all = "camelCase")]
 struct Options {
     plugins: Plugins,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scales: Option<Scales>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    datasets: Option<DatasetsOptions>,
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Scales {
+    x: Axis,
+    y: Axis,
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Axis {
+    stacked: bool,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<Title>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DatasetsOptions {
+    bar: BarOptions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BarOptions {
+    bar_percentage: f32,
+    category_percentage: f32,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct Plugins {
     title: Title,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    legend: Option<Legend>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Legend {
+    display: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -150,8 +192,76 @@ impl Chart {
                         display: true,
                         text: title,
                     },
+                    legend: None,
                 },
+                scales: None,
+                datasets: None,
+            },
+        }
+    }
+
+    /// A heatmap made with a stacked bar chart: every row is a dataset with a cell for every
+    /// label, the cells have the same height and the intensity of the color depends on the value.
+    /// `rows` are given from the bottom and the intensity is logarithmic, so that small values are
+    /// visible next to big ones.
+    pub fn heatmap(
+        title: String,
+        labels: Vec<String>,
+        rows: Vec<(String, Vec<u64>)>,
+        y_title: &str,
+    ) -> Chart {
+        let len = labels.len();
+        let max = rows.iter().flat_map(|(_, r)| r.iter()).max().cloned();
+        let max_ln = (max.unwrap_or(0) as f32).ln_1p().max(f32::MIN_POSITIVE);
+        let mut chart = Chart::new(title, Kind::Bar, labels);
+        chart.set_stacked();
+        if let Some(scales) = chart.options.scales.as_mut() {
+            scales.y.title = Some(Title {
+                display: true,
+                text: y_title.to_string(),
+            });
+        }
+        chart.options.plugins.legend = Some(Legend { display: false });
+        chart.options.datasets = Some(DatasetsOptions {
+            bar: BarOptions {
+                bar_percentage: 1.0,
+                category_percentage: 1.0,
             },
+        });
+        for (label, row) in rows {
+            let dataset = Dataset {
+                label,
+                data: vec![1; len],
+                background_color: row
+                    .iter()
+                    .map(|v| Color::Custom(0x33, 0x59, 0xAA, (*v as f32).ln_1p() / max_ln))
+                    .collect(),
+                border_color: vec![],
+                fill: true,
+                ..Default::default()
+            };
+            chart.add_dataset(dataset, None);
+        }
+        chart
+    }
+
+    /// stack the datasets on top of each other, used with `fill` datasets to show shares
+    pub fn set_stacked(&mut self) {
+        let scales = self.options.scales.get_or_insert_with(Scales::default);
+        scales.x.stacked = true;
+        scales.y.stacked = true;
+    }
+
+    /// use a logarithmic y axis, for values spanning many orders of magnitude
+    pub fn set_log_scale(&mut self) {
+        let scales = self.options.scales.get_or_insert_with(Scales::default);
+        scales.y.kind = Some("logarithmic".to_string());
+    }
+
+    /// fill the area of the datasets already added
+    pub fn set_fill(&mut self) {
+        for dataset in self.data.datasets.iter_mut() {
+            dataset.fill = true;
         }
     }
 
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Counter(Vec<u64>);
@@ -34,6 +35,58 @@ impl Counter {
     pub fn sum_all(&self) -> u64 {
         self.0.iter().sum()
     }
+
+    /// return a copy extended with zeros up to `len` elements
+    fn padded(&self, len: usize) -> Counter {
+        let mut counter = self.clone();
+        if len > counter.0.len() {
+            counter.0.resize(len, 0);
+        }
+        counter
+    }
+}
+
+/// Counters per period indexed by a key, useful when the set of keys is known only at runtime.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CounterMap(HashMap<String, Counter>);
+
+impl CounterMap {
+    pub fn add(&mut self, key: &str, index: usize, value: u64) {
+        match self.0.get_mut(key) {
+            Some(counter) => counter.add(index, value),
+            None => {
+                let mut counter = Counter::new();
+                counter.add(index, value);
+                self.0.insert(key.to_string(), counter);
+            }
+        }
+    }
+
+    pub fn increment(&mut self, key: &str, index: usize) {
+        self.add(key, index, 1)
+    }
+
+    /// like [`Counter::finish`] for every key, a counter not incremented in the last periods is
+    /// padded with zeros so that every returned vec has the same length
+    pub fn finish(&self) -> (BTreeMap<String, Vec<u64>>, usize) {
+        let len = self.0.values().map(|c| c.0.len()).max().unwrap_or(0);
+        let mut mul = 1;
+        let mut result = BTreeMap::new();
+        for (key, counter) in self.0.iter() {
+            let (vec, m) = counter.padded(len).finish();
+            mul = m;
+            result.insert(key.clone(), vec);
+        }
+        (result, mul)
+    }
+
+    /// total for every key, see [`Counter::sum_all`]
+    pub fn sum_all(&self) -> HashMap<String, u64> {
+        self.0
+            .iter()
+            .map(|(key, counter)| (key.clone(), counter.sum_all()))
+            .collect()
+    }
 }
 
 fn merge(data: &[u64]) -> Vec<u64> {
@@ -81,6 +134,16 @@ pub fn cumulative(data: &[u64]) -> Vec<u64> {
     result
 }
 
+/// the level at the end of every period given what is added and removed in the period,
+/// eg. the UTXO set size given the created and spent outputs
+pub fn level(added: &[u64], removed: &[u64]) -> Vec<u64> {
+    cumulative(added)
+        .iter()
+        .zip(cumulative(removed).iter())
+        .map(|(a, r)| a.saturating_sub(*r))
+        .collect()
+}
+
 impl Default for Counter {
     fn default() -> Self {
         Counter::new()
@@ -1,5 +1,8 @@
-use crate::process::{ProcessBip158Stats, ProcessOpRet, ProcessStats, ProcessTxStats};
-use blocks_iterator::log::info;
+use crate::process::{
+    ProcessBip158Stats, ProcessNonce, ProcessOpRet, ProcessPubkeys, ProcessStats, ProcessTxStats,
+    ProcessUtxo,
+};
+use blocks_iterator::log::{info, warn};
 use blocks_iterator::{PeriodCounter, PipeIterator};
 use chrono::format::StrftimeItems;
 use chrono::Utc;
@@ -23,9 +26,33 @@ struct Params {
     #[clap(short, long)]
     pub target_dir: PathBuf,
 
-    /// Parse pubkeys (which is expensive involving EC cryptography)
-    #[clap(short, long)]
+    /// Deprecated and ignored, public keys are always parsed
+    #[clap(short, long, hide = true)]
     pub parse_pubkeys: bool,
+
+    /// Megabytes of memory used to remember the scripts already seen, for address reuse stats,
+    /// the scripts not fitting are kept on disk in the raw directory, 8 bytes each
+    #[clap(long, default_value = "1024")]
+    pub seen_scripts_mb: usize,
+
+    /// Minimum age in years of the spent outputs counted as awakened dormant coins
+    #[clap(long, default_value = "5")]
+    pub dormant_years: u32,
+
+    /// Megabytes of memory used for the UTXO set, the outputs not fitting are kept on disk in the
+    /// raw directory. Without, the whole UTXO set is kept in memory, about 45 bytes per output
+    #[clap(long)]
+    pub utxo_mb: Option<usize>,
+
+    /// Megabytes of memory used to remember the R values of the ECDSA signatures already seen,
+    /// the values not fitting are kept on disk in the raw directory, 20 bytes each
+    #[clap(long, default_value = "512")]
+    pub seen_r_mb: usize,
+
+    /// Megabytes of memory used to remember the public keys already seen, for key reuse stats,
+    /// the keys not fitting are kept on disk in the raw directory, 9 bytes each
+    #[clap(long, default_value = "512")]
+    pub seen_pubkeys_mb: usize,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -34,6 +61,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("start");
 
     let params = Params::parse();
+    if params.parse_pubkeys {
+        warn!("--parse-pubkeys is deprecated and ignored, public keys are always parsed");
+    }
     if !fs::metadata(&params.target_dir).unwrap().is_dir() {
         panic!("--target-dir must be a directory");
     }
@@ -56,20 +86,39 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (send_2, receive_2) = sync_channel(blocks_size);
     let (send_3, receive_3) = sync_channel(blocks_size);
     let (send_4, receive_4) = sync_channel(blocks_size);
-    let senders = [send_1, send_2, send_3, send_4];
+    let (send_5, receive_5) = sync_channel(blocks_size);
+    let (send_6, receive_6) = sync_channel(blocks_size);
+    let (send_7, receive_7) = sync_channel(blocks_size);
+    let senders = [send_1, send_2, send_3, send_4, send_5, send_6, send_7];
 
-    let process = ProcessOpRet::new(receive_1, &params.target_dir, params.parse_pubkeys);
+    let process = ProcessOpRet::new(receive_1, &params.target_dir);
     let process_handle = thread::spawn(move || process.start());
 
     let process_stats = ProcessStats::new(receive_2, &params.target_dir);
     let process_stats_handle = thread::spawn(move || process_stats.start());
 
-    let process_bip158 = ProcessBip158Stats::new(receive_3, &params.target_dir);
+    let process_bip158 =
+        ProcessBip158Stats::new(receive_3, &params.target_dir, params.seen_scripts_mb);
     let process_bip158_handle = thread::spawn(move || process_bip158.start());
 
     let process_tx_stats = ProcessTxStats::new(receive_4, &params.target_dir);
     let process_tx_stats_handle = thread::spawn(move || process_tx_stats.start());
 
+    let process_utxo = ProcessUtxo::new(
+        receive_5,
+        &params.target_dir,
+        params.dormant_years,
+        params.utxo_mb,
+    );
+    let process_utxo_handle = thread::spawn(move || process_utxo.start());
+
+    let process_nonce = ProcessNonce::new(receive_6, &params.target_dir, params.seen_r_mb);
+    let process_nonce_handle = thread::spawn(move || process_nonce.start());
+
+    let process_pubkeys =
+        ProcessPubkeys::new(receive_7, &params.target_dir, params.seen_pubkeys_mb);
+    let process_pubkeys_handle = thread::spawn(move || process_pubkeys.start());
+
     let mut period = PeriodCounter::new(Duration::from_secs(10));
 
     for block_extra in iter {
@@ -95,6 +144,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let process_stats = process_stats_handle.join().expect("couldn't join");
     let (opret, script_type) = process_handle.join().expect("couldn't join");
     let tx_stats = process_tx_stats_handle.join().expect("couldn't join");
+    let utxo_stats = process_utxo_handle.join().expect("couldn't join");
+    let nonce_stats = process_nonce_handle.join().expect("couldn't join");
+    let pubkey_stats = process_pubkeys_handle.join().expect("couldn't join");
 
     let pages = pages::get_pages(
         &bip158_stats,
@@ -102,6 +154,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         &script_type,
         &process_stats,
         &tx_stats,
+        &utxo_stats,
+        &nonce_stats,
+        &pubkey_stats,
     );
     for page in pages.iter() {
         let page_html = page.to_html().into_string();
@@ -0,0 +1,60 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_1000;
+use crate::pages::{index_block, Page};
+use crate::process::Bip158Stats;
+
+pub fn address_reuse(bip158: &Bip158Stats) -> Page {
+    let (outputs, mul) = bip158.outputs_per_type.finish();
+    let (reused, _) = bip158.reused_outputs_per_type.finish();
+    let len = outputs.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+
+    let mut all_outputs = vec![0u64; len];
+    let mut all_reused = vec![0u64; len];
+    let mut chart_per_type = Chart::new(
+        "Outputs paying a script already used, by script type [‰]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    for ((script_type, outputs), color) in outputs.iter().zip(Color::rainbow()) {
+        let zeros = vec![0u64; len];
+        let reused = reused.get(script_type).unwrap_or(&zeros);
+        for i in 0..len {
+            all_outputs[i] += outputs[i];
+            all_reused[i] += reused[i];
+        }
+        let dataset = Dataset {
+            label: script_type.clone(),
+            data: perc_1000(reused, outputs),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: false,
+            ..Default::default()
+        };
+        chart_per_type.add_dataset(dataset, None);
+    }
+
+    let mut chart = Chart::new(
+        "Outputs paying a script already used [‰]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "reused".to_string(),
+        data: perc_1000(&all_reused, &all_outputs),
+        background_color: vec![Color::Red],
+        border_color: vec![Color::Red],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+
+    Page {
+        title: "Address reuse".to_string(),
+        description: "Per mille of spendable outputs paying to a script which has already been received or spent".to_string(),
+        permalink: "address-reuse".to_string(),
+        charts: vec![chart, chart_per_type],
+        tables: vec![],
+        text: "Scripts seen are remembered by a 64 bits hash, the chance of an output wrongly counted as reused because of a collision is negligible".to_string(),
+    }
+}
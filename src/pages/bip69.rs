@@ -64,6 +64,7 @@ pub fn bip69(tx_stats: &TxStats) -> Page {
         description: "BIP69 compliance, ordered transaction inputs and outputs".to_string(),
         permalink: "bip69".to_string(),
         charts,
+        tables: vec![],
         text: "".to_string(),
     }
 }
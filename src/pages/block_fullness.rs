@@ -0,0 +1,96 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
+use crate::pages::{index_block, Page};
+use crate::process::{Stats, FULLNESS_BUCKETS};
+
+pub fn block_fullness(stats: &Stats) -> Page {
+    let (blocks, mul) = stats.blocks_per_period.finish();
+    let (empty, _) = stats.empty_blocks.finish();
+    let labels: Vec<_> = (0..blocks.len()).map(|i| index_block(i, mul)).collect();
+
+    let mut chart_empty = Chart::new(
+        "Empty blocks, containing only the coinbase [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "empty blocks".to_string(),
+        data: perc_100(&empty, &blocks),
+        background_color: vec![Color::Grey],
+        border_color: vec![Color::Grey],
+        fill: false,
+        ..Default::default()
+    };
+    chart_empty.add_dataset(dataset, None);
+
+    let (fullness, _) = stats.fullness.finish();
+    let zeros = vec![0u64; blocks.len()];
+    let mut chart = Chart::new(
+        "Blocks by weight utilization [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    chart.set_stacked();
+    for (bucket, color) in FULLNESS_BUCKETS.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: bucket.to_string(),
+            data: perc_100(fullness.get(*bucket).unwrap_or(&zeros), &blocks),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+
+    let bucket_labels: Vec<_> = FULLNESS_BUCKETS.iter().map(|b| b.to_string()).collect();
+    let totals = stats.fullness.sum_all();
+    let mut chart_histogram = Chart::new(
+        "Blocks by weight utilization [-]".to_string(),
+        Kind::Bar,
+        bucket_labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "blocks".to_string(),
+        data: FULLNESS_BUCKETS
+            .iter()
+            .map(|b| totals.get(*b).cloned().unwrap_or(0))
+            .collect(),
+        background_color: vec![Color::Blue],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart_histogram.add_dataset(dataset, None);
+
+    let mut chart_fees = Chart::new(
+        "Average fee rate by block weight utilization [sat/kvB]".to_string(),
+        Kind::Bar,
+        bucket_labels,
+    );
+    let dataset = Dataset {
+        label: "fee rate".to_string(),
+        data: FULLNESS_BUCKETS
+            .iter()
+            .map(|b| {
+                let fee = stats.fee_by_fullness.get(*b).cloned().unwrap_or(0);
+                let vsize = stats.vsize_by_fullness.get(*b).cloned().unwrap_or(0);
+                fee * 1000 / vsize.max(1)
+            })
+            .collect(),
+        background_color: vec![Color::Orange],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart_fees.add_dataset(dataset, None);
+
+    Page {
+        title: "Block fullness".to_string(),
+        description: "Weight utilization of the blocks, empty blocks and the fees paid in full and empty blocks".to_string(),
+        permalink: "block-fullness".to_string(),
+        charts: vec![chart_empty, chart, chart_histogram, chart_fees],
+        tables: vec![],
+        text: "Weight utilization is the block weight compared to the limit of 4 million weight units. The average fee rate is the total fees of the blocks divided by their virtual size, including the coinbase, in satoshi per 1000 virtual bytes to show the fee rates lower than 1 sat/vB of the early blocks".to_string(),
+    }
+}
@@ -0,0 +1,99 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
+use crate::pages::{index_block, Page};
+use crate::process::{Stats, MAX_BLOCK_WEIGHT};
+
+pub fn blockspace(stats: &Stats) -> Page {
+    let (size, mul) = stats.block_size_per_period.finish();
+    let (weight, _) = stats.block_weight_per_period.finish();
+    let (stripped, _) = stats.stripped_size_per_period.finish();
+    let (blocks, _) = stats.blocks_per_period.finish();
+    let labels: Vec<_> = (0..size.len()).map(|i| index_block(i, mul)).collect();
+    let vsize: Vec<_> = weight.iter().map(|w| w.div_ceil(4)).collect();
+    let witness: Vec<_> = size
+        .iter()
+        .zip(stripped.iter())
+        .map(|(s, b)| s - b)
+        .collect();
+
+    let mut chart = Chart::new(
+        "Size, virtual size and weight of the blocks [MB, MvB, MWU]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let series = [
+        ("size [MB]", &size, Color::Blue),
+        ("virtual size [MvB]", &vsize, Color::Green),
+        ("weight [MWU]", &weight, Color::Orange),
+    ];
+    for (label, data, color) in series.iter() {
+        let dataset = Dataset {
+            label: label.to_string(),
+            data: data.iter().map(|v| v / 1_000_000).collect(),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+
+    let mut chart_saved = Chart::new(
+        "Bytes saved by the segwit discount, size minus virtual size [MB]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "saved".to_string(),
+        data: size
+            .iter()
+            .zip(vsize.iter())
+            .map(|(s, v)| s.saturating_sub(*v) / 1_000_000)
+            .collect(),
+        background_color: vec![Color::Green],
+        border_color: vec![Color::Green],
+        fill: false,
+        ..Default::default()
+    };
+    chart_saved.add_dataset(dataset, None);
+
+    let mut chart_utilization = Chart::new(
+        "Average block weight utilization [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let max_weight: Vec<_> = blocks.iter().map(|b| b * MAX_BLOCK_WEIGHT).collect();
+    let dataset = Dataset {
+        label: "weight utilization".to_string(),
+        data: perc_100(&weight, &max_weight),
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart_utilization.add_dataset(dataset, None);
+
+    let mut chart_witness = Chart::new(
+        "Weight spent on witnesses [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "witness".to_string(),
+        data: perc_100(&witness, &weight),
+        background_color: vec![Color::Purple],
+        border_color: vec![Color::Purple],
+        fill: false,
+        ..Default::default()
+    };
+    chart_witness.add_dataset(dataset, None);
+
+    Page {
+        title: "Blockspace".to_string(),
+        description: "Weight of the blocks, savings of the segwit discount and witness share".to_string(),
+        permalink: "blockspace".to_string(),
+        charts: vec![chart, chart_saved, chart_utilization, chart_witness],
+        tables: vec![],
+        text: "Weight is 4 weight units for every byte serialized without witnesses plus 1 for every witness byte, virtual size is a quarter of the weight. Blocks are limited to 4 million weight units. Witness bytes include the segwit marker and flag".to_string(),
+    }
+}
@@ -0,0 +1,66 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::CounterMap;
+use crate::pages::{index_block, Page};
+use crate::process::{TxStats, COINJOIN_KINDS};
+
+pub fn coinjoin(tx_stats: &TxStats) -> Page {
+    let chart = kinds_chart(
+        "CoinJoin transactions [-]",
+        &tx_stats.coinjoin_per_period,
+        1,
+    );
+    let chart_volume = kinds_chart(
+        "Value of the equal outputs of CoinJoin transactions [bitcoin]",
+        &tx_stats.coinjoin_volume,
+        100_000_000,
+    );
+    let chart_participants = kinds_chart(
+        "Estimated participants of CoinJoin transactions [-]",
+        &tx_stats.coinjoin_participants,
+        1,
+    );
+
+    Page {
+        title: "CoinJoin".to_string(),
+        description: "Transactions mixing the coins of many participants, by coordinator software".to_string(),
+        permalink: "coinjoin".to_string(),
+        charts: vec![chart, chart_volume, chart_participants],
+        tables: vec![],
+        text: "CoinJoins are detected as transactions with at least 3 outputs of the same value and at least as many inputs. Whirlpool has 5 inputs and 5 outputs of the pool value, Wasabi 1 many outputs of about 0.1 bitcoin, Wasabi 2 at least 50 inputs and outputs mostly in shared denominations, JoinMarket up to 20 equal outputs with at most a change output for every participant. Participants are estimated as the number of equal outputs".to_string(),
+    }
+}
+
+/// Stacked chart with a dataset per CoinJoin kind, values divided by `unit`
+fn kinds_chart(title: &str, counters: &CounterMap, unit: u64) -> Chart {
+    let (map, mul) = counters.finish();
+    let len = map.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+
+    let mut chart = Chart::new(title.to_string(), Kind::Line, labels);
+    chart.set_stacked();
+    let colors = [
+        Color::Blue,
+        Color::Green,
+        Color::Orange,
+        Color::Purple,
+        Color::Grey,
+    ];
+    for (kind, color) in COINJOIN_KINDS.iter().zip(colors.iter()) {
+        let dataset = Dataset {
+            label: kind.to_string(),
+            data: map
+                .get(*kind)
+                .unwrap_or(&zeros)
+                .iter()
+                .map(|v| v / unit)
+                .collect(),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    chart
+}
@@ -0,0 +1,179 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::pages::{btc, counter_map_chart, index_block, tx_link, Page, Table};
+use crate::process::UtxoStats;
+use maud::html;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn dormant_coins(utxo_stats: &UtxoStats) -> Page {
+    let mut charts = vec![];
+
+    let years: BTreeSet<_> = utxo_stats
+        .tip_p2pk_per_year
+        .keys()
+        .chain(utxo_stats.tip_uncompressed_per_year.keys())
+        .cloned()
+        .collect();
+    let labels: Vec<_> = years.into_iter().collect();
+    let by_year = |map: &BTreeMap<String, u64>| -> Vec<u64> {
+        labels
+            .iter()
+            .map(|y| map.get(y).cloned().unwrap_or(0))
+            .collect()
+    };
+
+    let mut chart = Chart::new(
+        "P2PK UTXO at the tip by year of creation [-]".to_string(),
+        Kind::Bar,
+        labels.clone(),
+    );
+    let datasets = [
+        ("p2pk", &utxo_stats.tip_p2pk_per_year, Color::Purple),
+        (
+            "coinbase p2pk",
+            &utxo_stats.tip_coinbase_p2pk_per_year,
+            Color::Orange,
+        ),
+        (
+            "uncompressed p2pk",
+            &utxo_stats.tip_uncompressed_per_year,
+            Color::Red,
+        ),
+    ];
+    for (label, map, color) in datasets.iter() {
+        let dataset = Dataset {
+            label: label.to_string(),
+            data: by_year(map),
+            background_color: vec![*color],
+            border_color: vec![],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let mut chart = Chart::new(
+        "P2PK UTXO value at the tip by year of creation [bitcoin]".to_string(),
+        Kind::Bar,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "p2pk".to_string(),
+        data: by_year(&utxo_stats.tip_p2pk_value_per_year)
+            .iter()
+            .map(|sat| sat / 100_000_000)
+            .collect(),
+        background_color: vec![Color::Purple],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    let (awakened, mul) = utxo_stats.awakened.finish();
+    let len = awakened.get("all").map(|v| v.len()).unwrap_or(0);
+    let period_labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+    let mut chart = Chart::new(
+        format!(
+            "Awakened outputs older than {} years [-]",
+            utxo_stats.dormant_years
+        ),
+        Kind::Line,
+        period_labels.clone(),
+    );
+    let datasets = [
+        ("all", Color::Blue),
+        ("p2pk", Color::Purple),
+        ("uncompressed p2pk", Color::Red),
+    ];
+    for (label, color) in datasets.iter() {
+        let dataset = Dataset {
+            label: label.to_string(),
+            data: awakened.get(*label).unwrap_or(&zeros).clone(),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let mut chart = Chart::new(
+        format!(
+            "Value of the awakened outputs older than {} years [bitcoin]",
+            utxo_stats.dormant_years
+        ),
+        Kind::Line,
+        period_labels,
+    );
+    let dataset = Dataset {
+        label: "value".to_string(),
+        data: utxo_stats
+            .awakened_value
+            .finish()
+            .0
+            .iter()
+            .map(|sat| sat / 100_000_000)
+            .collect(),
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    charts.push(counter_map_chart(
+        "Awakened outputs by year of creation [-]",
+        &utxo_stats.awakened_per_year,
+    ));
+
+    let rows = utxo_stats
+        .notable_awakened
+        .iter()
+        .map(|a| {
+            vec![
+                html! { (a.height) },
+                tx_link(&a.txid),
+                html! { (btc(a.value)) },
+                html! { (a.created_height) " (" (a.created_year) ")" },
+                html! { (a.script_type) },
+            ]
+        })
+        .collect();
+    let table = Table {
+        title: format!(
+            "Largest spends of outputs older than {} years",
+            utxo_stats.dormant_years
+        ),
+        header: vec![
+            "Height".to_string(),
+            "Transaction".to_string(),
+            "Value [bitcoin]".to_string(),
+            "Created at height (year)".to_string(),
+            "Script type".to_string(),
+        ],
+        rows,
+    };
+
+    let before_2011 = |map: &BTreeMap<String, u64>| -> u64 {
+        map.range(.."2011".to_string()).map(|(_, v)| v).sum()
+    };
+
+    Page {
+        title: "Dormant coins".to_string(),
+        description: "P2PK outputs by year of creation and spends of long dormant outputs"
+            .to_string(),
+        permalink: "dormant-coins".to_string(),
+        charts,
+        tables: vec![table],
+        text: format!(
+            "Unspent coinbase P2PK outputs created before 2011: {}, value of the P2PK outputs created before 2011: {} bitcoin",
+            before_2011(&utxo_stats.tip_coinbase_p2pk_per_year),
+            before_2011(&utxo_stats.tip_p2pk_value_per_year) / 100_000_000
+        ),
+    }
+}
@@ -0,0 +1,116 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::pages::{index_block, Page};
+use crate::process::{Stats, FEE_RATE_BUCKETS};
+
+pub fn fee_market(stats: &Stats) -> Page {
+    let (fee_rates, mul) = stats.fee_rates.finish();
+    let len = fee_rates.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+    let buckets: Vec<_> = FEE_RATE_BUCKETS
+        .iter()
+        .map(|(_, label)| fee_rates.get(*label).unwrap_or(&zeros))
+        .collect();
+    let histograms: Vec<Vec<u64>> = (0..len)
+        .map(|i| buckets.iter().map(|b| b[i]).collect())
+        .collect();
+
+    let mut chart = Chart::new(
+        "Fee rate percentiles of the transactions [sat/vB]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let percentiles = [(10, Color::Green), (50, Color::Blue), (90, Color::Orange)];
+    for (p, color) in percentiles.iter() {
+        let dataset = Dataset {
+            label: format!("p{}", p),
+            data: histograms
+                .iter()
+                .map(|h| percentile(h, *p as f64 / 100.0))
+                .collect(),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    let no_last = &stats.max_fee_rate[..stats.max_fee_rate.len().saturating_sub(1)];
+    let dataset = Dataset {
+        label: "max".to_string(),
+        data: no_last
+            .chunks(mul)
+            .map(|c| c.iter().max().cloned().unwrap_or(0))
+            .take(len)
+            .collect(),
+        background_color: vec![Color::Red],
+        border_color: vec![Color::Red],
+        fill: false,
+        hidden: true,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+
+    let totals = stats.fee_rates.sum_all();
+    let mut chart_histogram = Chart::new(
+        "Transactions by fee rate [sat/vB]".to_string(),
+        Kind::Bar,
+        FEE_RATE_BUCKETS
+            .iter()
+            .map(|(_, l)| l.to_string())
+            .collect(),
+    );
+    let dataset = Dataset {
+        label: "transactions".to_string(),
+        data: FEE_RATE_BUCKETS
+            .iter()
+            .map(|(_, l)| totals.get(*l).cloned().unwrap_or(0))
+            .collect(),
+        background_color: vec![Color::Blue],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart_histogram.add_dataset(dataset, None);
+
+    Page {
+        title: "Fee market".to_string(),
+        description: "Distribution of the fee rate paid by the transactions".to_string(),
+        permalink: "fee-market".to_string(),
+        charts: vec![chart, chart_histogram],
+        tables: vec![],
+        text: "Percentiles are computed from the fee rate buckets of the histogram and show the lower bound of the bucket. Coinbase transactions are excluded".to_string(),
+    }
+}
+
+/// The lower bound of the bucket containing the `p` fraction of the transactions, `histogram`
+/// contains the number of transactions in every bucket of [`FEE_RATE_BUCKETS`]
+fn percentile(histogram: &[u64], p: f64) -> u64 {
+    let total: u64 = histogram.iter().sum();
+    let mut cumulative = 0;
+    let mut lower_bound = 0;
+    for (count, (limit, _)) in histogram.iter().zip(FEE_RATE_BUCKETS.iter()) {
+        cumulative += count;
+        if total > 0 && cumulative as f64 >= p * total as f64 {
+            return lower_bound;
+        }
+        lower_bound = *limit;
+    }
+    0
+}
+
+#[cfg(test)]
+mod test {
+    use super::percentile;
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 0.5), 0);
+        assert_eq!(percentile(&[0, 0, 0], 0.5), 0);
+        assert_eq!(percentile(&[10], 0.9), 0);
+        assert_eq!(percentile(&[1, 1, 8], 0.1), 0);
+        assert_eq!(percentile(&[1, 1, 8], 0.2), 1);
+        assert_eq!(percentile(&[1, 1, 8], 0.5), 2);
+        assert_eq!(percentile(&[0, 0, 0, 0, 0, 5, 5], 0.9), 10);
+    }
+}
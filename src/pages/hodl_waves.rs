@@ -0,0 +1,109 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
+use crate::pages::{index_block, Page};
+use crate::process::{UtxoStats, AGE_BUCKETS};
+
+pub fn hodl_waves(utxo_stats: &UtxoStats) -> Page {
+    let mut charts = vec![];
+
+    let (waves, mul) = utxo_stats.hodl_waves.finish();
+    let len = waves.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let mut total = vec![0u64; len];
+    for wave in waves.values() {
+        for (t, v) in total.iter_mut().zip(wave.iter()) {
+            *t += v;
+        }
+    }
+
+    let mut chart = Chart::new(
+        "HODL waves, UTXO value by age [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    chart.set_stacked();
+    let zeros = vec![0u64; len];
+    for ((_, bucket), color) in AGE_BUCKETS.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: bucket.to_string(),
+            data: perc_100(waves.get(*bucket).unwrap_or(&zeros), &total),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let (spent, mul) = utxo_stats.spent_per_age.finish();
+    let len = spent.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let mut chart = Chart::new(
+        "Spent outputs by age [-]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    chart.set_stacked();
+    let zeros = vec![0u64; len];
+    for ((_, bucket), color) in AGE_BUCKETS.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: bucket.to_string(),
+            data: spent.get(*bucket).unwrap_or(&zeros).clone(),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let (vec, mul) = utxo_stats.coin_days_destroyed.finish();
+    let labels: Vec<_> = (0..vec.len()).map(|i| index_block(i, mul)).collect();
+    let mut chart = Chart::new(
+        "Coin days destroyed [bitcoin days]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "coin days destroyed".to_string(),
+        data: vec,
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    let labels: Vec<_> = AGE_BUCKETS.iter().map(|(_, b)| b.to_string()).collect();
+    let mut chart = Chart::new(
+        "UTXO value at the tip by age [bitcoin]".to_string(),
+        Kind::Bar,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "value".to_string(),
+        data: labels
+            .iter()
+            .map(|b| utxo_stats.tip_ages.get(b).cloned().unwrap_or(0) / 100_000_000)
+            .collect(),
+        background_color: vec![Color::Purple],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    Page {
+        title: "HODL waves".to_string(),
+        description: "Age of the coins, in the UTXO set and when spent, and coin days destroyed"
+            .to_string(),
+        permalink: "hodl-waves".to_string(),
+        charts,
+        tables: vec![],
+        text: "".to_string(),
+    }
+}
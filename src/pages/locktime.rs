@@ -1,7 +1,7 @@
 use crate::charts::{Chart, Color, Dataset, Kind};
-use crate::counter::cumulative;
-use crate::pages::{to_label_map, Page};
-use crate::process::TxStats;
+use crate::counter::{cumulative, perc_100};
+use crate::pages::{index_block, to_label_map, Page};
+use crate::process::{TxStats, LOCKTIME_CLASSES};
 
 pub fn locktime(tx_stats: &TxStats) -> Page {
     let title = "Number of tx non-deeply reorgable".to_string();
@@ -14,7 +14,7 @@ pub fn locktime(tx_stats: &TxStats) -> Page {
     let mut charts = vec![];
 
     let labels: Vec<_> = no_reorg.keys().cloned().collect();
-    let mut chart = Chart::new(title.clone(), Kind::Line, labels);
+    let mut chart = Chart::new(title, Kind::Line, labels);
 
     let dataset = Dataset {
         label: "non reorgable".to_string(),
@@ -38,13 +38,37 @@ pub fn locktime(tx_stats: &TxStats) -> Page {
 
     charts.push(chart);
 
+    let (classes, mul) = tx_stats.locktime_per_period.finish();
+    let len = classes.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+    let (total, _) = tx_stats.non_coinbase_tx_per_period.finish();
+
+    let mut chart_classes = Chart::new(
+        "Transactions by nLockTime [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    chart_classes.set_stacked();
+    for (class, color) in LOCKTIME_CLASSES.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: class.to_string(),
+            data: perc_100(classes.get(*class).unwrap_or(&zeros), &total),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: true,
+            ..Default::default()
+        };
+        chart_classes.add_dataset(dataset, None);
+    }
+    charts.push(chart_classes);
+
     Page {
-        title,
-        description:
-            "Number of transactions with nlocktime greater than confirmation height minus 6"
-                .to_string(),
+        title: "nLockTime usage".to_string(),
+        description: "Number of transactions with nlocktime greater than confirmation height minus 6, anti fee sniping and other nlocktime patterns".to_string(),
         permalink: "locktime".to_string(),
         charts,
-        text: "".to_string(),
+        tables: vec![],
+        text: "tip height is the height of the block before the confirming one, as set by anti fee sniping wallets, which sometimes go back randomly up to 100 blocks. A locktime with the upper byte 0x20 is the obscured commitment number of a lightning commitment transaction. Future locktimes are valid only if every input is final. Coinbase transactions are excluded from the classification".to_string(),
     }
 }
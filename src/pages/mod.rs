@@ -1,37 +1,77 @@
+mod address_reuse;
 pub mod bip69;
+mod block_fullness;
 mod blockchain_sizes;
+mod blockspace;
+mod coinjoin;
+mod dormant_coins;
+mod fee_market;
+mod hodl_waves;
 mod locktime;
+mod nonce_reuse;
+mod nsequence;
 mod number_of_inputs_and_outputs;
 mod op_return;
 mod op_return_protocols;
 mod op_return_sizes;
+mod output_values;
+mod price;
+mod pubkey_formats;
+mod pubkey_reuse;
 mod rounded_amount;
 mod script_types;
 mod segwit_multisig;
 mod sighash_types;
+mod signature_sizes;
+mod spending_policies;
 mod spent_same_block;
 mod total_tx_outputs_inputs;
+mod tx_versions;
+mod utxo_set;
+mod wallet_fingerprints;
 mod witness_stats;
 
-use crate::charts::Chart;
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::CounterMap;
 use crate::now;
-use crate::process::{Bip158Stats, OpReturnData, ScriptType, Stats, TxStats};
+use crate::process::{
+    Bip158Stats, NonceStats, OpReturnData, PubkeyStats, ScriptType, Stats, TxStats, UtxoStats,
+};
+use blocks_iterator::bitcoin::Txid;
 use maud::{html, Markup, PreEscaped, DOCTYPE};
 use std::collections::{BTreeMap, HashMap};
 
+pub use address_reuse::address_reuse;
 pub use bip69::bip69;
+pub use block_fullness::block_fullness;
 pub use blockchain_sizes::blockchain_sizes;
+pub use blockspace::blockspace;
+pub use coinjoin::coinjoin;
+pub use dormant_coins::dormant_coins;
+pub use fee_market::fee_market;
+pub use hodl_waves::hodl_waves;
 pub use locktime::locktime;
+pub use nonce_reuse::nonce_reuse;
+pub use nsequence::nsequence;
 pub use number_of_inputs_and_outputs::number_of_inputs_and_outputs;
 pub use op_return::op_return_per_month;
 pub use op_return_protocols::op_return_protocols;
 pub use op_return_sizes::op_return_sizes;
+pub use output_values::output_values;
+pub use price::price;
+pub use pubkey_formats::pubkey_formats;
+pub use pubkey_reuse::pubkey_reuse;
 pub use rounded_amount::rounded_amount;
 pub use script_types::script_types;
 pub use segwit_multisig::segwit_multisig;
 pub use sighash_types::sighash_types;
+pub use signature_sizes::signature_sizes;
+pub use spending_policies::spending_policies;
 pub use spent_same_block::spent_same_block;
 pub use total_tx_outputs_inputs::total_tx_outputs_inputs;
+pub use tx_versions::tx_versions;
+pub use utxo_set::utxo_set;
+pub use wallet_fingerprints::wallet_fingerprints;
 pub use witness_stats::witness_stats;
 
 pub struct Page {
@@ -39,9 +79,17 @@ pub struct Page {
     pub description: String,
     pub permalink: String,
     pub charts: Vec<Chart>,
+    pub tables: Vec<Table>,
     pub text: String,
 }
 
+/// A table shown after the charts, cells are `Markup` so that they can contain links
+pub struct Table {
+    pub title: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Markup>>,
+}
+
 const NBSP: PreEscaped<&str> = PreEscaped("&nbsp;");
 
 /// Pages headers.
@@ -98,11 +146,49 @@ impl Page {
                 (chart.to_html())
                 p { (NBSP) }
             }
+            @for table in self.tables.iter() {
+                (table.to_html())
+                p { (NBSP) }
+            }
         };
         page(charts, &self.text)
     }
 }
 
+impl Table {
+    pub fn to_html(&self) -> Markup {
+        html! {
+            h3 { (self.title) }
+            table style="border-collapse: collapse; font-family: monospace;" {
+                tr {
+                    @for cell in self.header.iter() {
+                        th style="text-align: left; padding: 2px 8px;" { (cell) }
+                    }
+                }
+                @for row in self.rows.iter() {
+                    tr {
+                        @for cell in row.iter() {
+                            td style="padding: 2px 8px;" { (cell) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Link to the transaction in a block explorer
+pub fn tx_link(txid: &Txid) -> Markup {
+    html! {
+        a href=(format!("https://mempool.space/tx/{}", txid)) { (txid.to_string()) }
+    }
+}
+
+/// Satoshi amount formatted in bitcoin with 8 decimals
+pub fn btc(sat: u64) -> String {
+    format!("{}.{:08}", sat / 100_000_000, sat % 100_000_000)
+}
+
 pub fn create_index(pages: &[Page]) -> Markup {
     let links = html! {
         ul {
@@ -155,12 +241,60 @@ pub fn index_block(index: usize, mul: usize) -> String {
     format!("{:4}k-{}k", from, to)
 }
 
+/// Line chart with a dataset per key of `counters`, like [`map_by_value`] only the 10 keys with
+/// the highest totals are shown while the others are summed in "other"
+pub fn counter_map_chart(title: &str, counters: &CounterMap) -> Chart {
+    let (map, mul) = counters.finish();
+    let mut totals: Vec<_> = counters.sum_all().into_iter().collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let len = map.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels = (0..len).map(|i| index_block(i, mul)).collect();
+
+    let mut chart = Chart::new(title.to_string(), Kind::Line, labels);
+    let colors = Color::rainbow();
+    let mut other = vec![0u64; len];
+    for (i, (key, _)) in totals.iter().enumerate() {
+        let data = &map[key];
+        if i < 10 {
+            let dataset = Dataset {
+                label: key.clone(),
+                data: data.clone(),
+                background_color: vec![colors[i]],
+                border_color: vec![colors[i]],
+                fill: false,
+                ..Default::default()
+            };
+            chart.add_dataset(dataset, None);
+        } else {
+            for (o, d) in other.iter_mut().zip(data.iter()) {
+                *o += d;
+            }
+        }
+    }
+    if totals.len() > 10 {
+        let dataset = Dataset {
+            label: "other".to_string(),
+            data: other,
+            background_color: vec![Color::Grey],
+            border_color: vec![Color::Grey],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    chart
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn get_pages(
     bip158: &Bip158Stats,
     opret: &OpReturnData,
     script_type: &ScriptType,
     stats: &Stats,
     tx_stats: &TxStats,
+    utxo_stats: &UtxoStats,
+    nonce_stats: &NonceStats,
+    pubkey_stats: &PubkeyStats,
 ) -> Vec<Page> {
     let mut pages = vec![];
 
@@ -175,9 +309,27 @@ pub fn get_pages(
     pages.push(segwit_multisig(&script_type));
     pages.push(spent_same_block(&stats, &tx_stats));
     pages.push(sighash_types(&stats));
+    pages.push(signature_sizes(stats));
     pages.push(total_tx_outputs_inputs(&tx_stats));
     pages.push(bip69(&tx_stats));
     pages.push(locktime(&tx_stats));
+    pages.push(spending_policies(script_type));
+    pages.push(address_reuse(bip158));
+    pages.push(utxo_set(utxo_stats));
+    pages.push(hodl_waves(utxo_stats));
+    pages.push(dormant_coins(utxo_stats));
+    pages.push(nonce_reuse(nonce_stats));
+    pages.push(pubkey_formats(pubkey_stats));
+    pages.push(pubkey_reuse(pubkey_stats));
+    pages.push(fee_market(stats));
+    pages.push(nsequence(tx_stats));
+    pages.push(tx_versions(tx_stats));
+    pages.push(wallet_fingerprints(tx_stats));
+    pages.push(coinjoin(tx_stats));
+    pages.push(blockspace(stats));
+    pages.push(block_fullness(stats));
+    pages.push(price(stats));
+    pages.push(output_values(stats));
 
     pages
 }
@@ -0,0 +1,84 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_1000;
+use crate::pages::{index_block, tx_link, Page, Table};
+use crate::process::NonceStats;
+use maud::html;
+
+pub fn nonce_reuse(nonce_stats: &NonceStats) -> Page {
+    let (signatures, mul) = nonce_stats.signatures.finish();
+    let (reused, _) = nonce_stats.reused_r.finish();
+    let labels: Vec<_> = (0..signatures.len()).map(|i| index_block(i, mul)).collect();
+
+    let mut chart = Chart::new(
+        "ECDSA signatures with an R value already used [-]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "reused R".to_string(),
+        data: reused.clone(),
+        background_color: vec![Color::Red],
+        border_color: vec![Color::Red],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+
+    let mut chart_perc = Chart::new(
+        "ECDSA signatures with an R value already used [‰]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "reused R".to_string(),
+        data: perc_1000(&reused, &signatures),
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart_perc.add_dataset(dataset, None);
+
+    let most_reused = Table {
+        title: "R values used the most".to_string(),
+        header: vec!["R".to_string(), "Reuses".to_string()],
+        rows: nonce_stats
+            .most_reused
+            .iter()
+            .map(|(r, count)| vec![html! { (r) }, html! { (count) }])
+            .collect(),
+    };
+    let last_reused = Table {
+        title: "Last signatures with an R value already used".to_string(),
+        header: vec![
+            "Height".to_string(),
+            "Transaction".to_string(),
+            "Input".to_string(),
+            "R".to_string(),
+            "First used at height, transaction position, input".to_string(),
+        ],
+        rows: nonce_stats
+            .last_reused
+            .iter()
+            .rev()
+            .map(|r| {
+                vec![
+                    html! { (r.height) },
+                    tx_link(&r.txid),
+                    html! { (r.vin) },
+                    html! { (r.r) },
+                    html! { (r.first.height) ", " (r.first.tx_position) ", " (r.first.vin) },
+                ]
+            })
+            .collect(),
+    };
+
+    Page {
+        title: "ECDSA nonce reuse".to_string(),
+        description: "Signatures reusing the R value of a previous signature, leaking the private key".to_string(),
+        permalink: "nonce-reuse".to_string(),
+        charts: vec![chart, chart_perc],
+        tables: vec![most_reused, last_reused],
+        text: "R values seen are remembered by a 64 bits hash and the first 4 bytes of R, the chance of a signature wrongly counted as reusing R is negligible. The file reused_r.txt contains every signature reusing R with the position of the first signature using it".to_string(),
+    }
+}
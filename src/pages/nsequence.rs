@@ -0,0 +1,78 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
+use crate::pages::{index_block, Page};
+use crate::process::{TxStats, SEQUENCE_CLASSES};
+
+pub fn nsequence(tx_stats: &TxStats) -> Page {
+    let (classes, mul) = tx_stats.sequence_per_period.finish();
+    let len = classes.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+    let mut total = vec![0u64; len];
+    for class in classes.values() {
+        for (t, v) in total.iter_mut().zip(class.iter()) {
+            *t += v;
+        }
+    }
+
+    let mut chart = Chart::new(
+        "Inputs by nSequence [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    chart.set_stacked();
+    let colors = [
+        Color::Grey,
+        Color::Blue,
+        Color::Orange,
+        Color::Green,
+        Color::Purple,
+        Color::Yellow,
+    ];
+    for (class, color) in SEQUENCE_CLASSES.iter().zip(colors.iter()) {
+        let dataset = Dataset {
+            label: class.to_string(),
+            data: perc_100(classes.get(*class).unwrap_or(&zeros), &total),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+
+    let (txs, _) = tx_stats.non_coinbase_tx_per_period.finish();
+    let mut chart_txs = Chart::new(
+        "Transactions signalling RBF or enforcing a relative locktime [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let series = [
+        ("RBF", &tx_stats.rbf_signalling, Color::Orange),
+        (
+            "relative locktime",
+            &tx_stats.relative_locktime,
+            Color::Green,
+        ),
+    ];
+    for (label, counter, color) in series.iter() {
+        let dataset = Dataset {
+            label: label.to_string(),
+            data: perc_100(&counter.finish().0, &txs),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: false,
+            ..Default::default()
+        };
+        chart_txs.add_dataset(dataset, None);
+    }
+
+    Page {
+        title: "nSequence usage".to_string(),
+        description: "Adoption of RBF signalling, relative locktimes and other nSequence uses in the inputs".to_string(),
+        permalink: "nsequence".to_string(),
+        charts: vec![chart, chart_txs],
+        tables: vec![],
+        text: "final is 0xffffffff, locktime is 0xfffffffe, which enables nLockTime without signalling RBF. Relative locktimes are enforced by BIP68 in version 2 transactions only, while in lightning commitment transactions the upper byte is 0x80 and the rest is the obscured commitment number. Coinbase inputs are excluded".to_string(),
+    }
+}
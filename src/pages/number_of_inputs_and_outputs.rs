@@ -1,6 +1,7 @@
 use crate::charts::{Chart, Color, Dataset, Kind};
-use crate::pages::{map_by_value, Page};
-use crate::process::TxStats;
+use crate::counter::perc_100;
+use crate::pages::{index_block, map_by_value, Page};
+use crate::process::{TxStats, PAYMENT_CLASSES};
 
 pub fn number_of_inputs_and_outputs(stats: &TxStats) -> Page {
     let mut charts = vec![];
@@ -26,11 +27,79 @@ pub fn number_of_inputs_and_outputs(stats: &TxStats) -> Page {
 
     charts.push(chart);
 
+    let (classes, mul) = stats.payment_class_per_period.finish();
+    let len = classes.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+    let (txs, _) = stats.non_coinbase_tx_per_period.finish();
+
+    let mut chart = Chart::new(
+        "Transactions by payment class [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    chart.set_stacked();
+    let colors = [
+        Color::Blue,
+        Color::Green,
+        Color::Orange,
+        Color::Purple,
+        Color::Grey,
+    ];
+    for (class, color) in PAYMENT_CLASSES.iter().zip(colors.iter()) {
+        let dataset = Dataset {
+            label: class.to_string(),
+            data: perc_100(classes.get(*class).unwrap_or(&zeros), &txs),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let mut chart = Chart::new(
+        "Payments per 100 transactions [-]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "payments".to_string(),
+        data: perc_100(&stats.payments_per_period.finish().0, &txs),
+        background_color: vec![Color::Blue],
+        border_color: vec![Color::Blue],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    let mut chart = Chart::new(
+        "Blockspace saved by batching, compared to the used blockspace [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "saved".to_string(),
+        data: perc_100(
+            &stats.batching_saved_vbytes.finish().0,
+            &stats.vsize_per_period.finish().0,
+        ),
+        background_color: vec![Color::Green],
+        border_color: vec![Color::Green],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
     Page {
         title: "Number of inputs and outputs".to_string(),
-        description: "Show how many txs have A input and B output".to_string(),
+        description: "Show how many txs have A input and B output, payment batching and consolidation".to_string(),
         permalink: "number-of-inputs-and-outputs".to_string(),
         charts,
-        text: "".to_string(),
+        tables: vec![],
+        text: "Payments are the spendable outputs of a transaction excluding one change output when there are at least two. Consolidations merge at least 3 inputs in a single output, fan-outs split a single input in at least 10 outputs. The blockspace saved by a batch is estimated as a transaction for every payment but one, each with an input, a change output and the transaction overhead of the average size in the batch".to_string(),
     }
 }
@@ -75,6 +75,7 @@ pub fn op_return_per_month(opret: &OpReturnData) -> Page {
             .to_string(),
         permalink: "op-return".to_string(),
         charts,
+        tables: vec![],
         text: "".to_string(),
     }
 }
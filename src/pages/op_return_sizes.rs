@@ -63,6 +63,7 @@ pub fn op_return_sizes(opret: &OpReturnData) -> Page {
         description: description.to_string(),
         permalink: "op-return-sizes".to_string(),
         charts,
+        tables: vec![],
         text: "".to_string(),
     }
 }
@@ -0,0 +1,76 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::{perc_100, perc_1000, Counter};
+use crate::pages::{index_block, Page};
+use crate::process::{Stats, BINS_PER_LN, DUST_VALUE, VALUE_BINS_PER_ROW, WHALE_VALUE};
+
+pub fn output_values(stats: &Stats) -> Page {
+    let (outputs, mul) = stats.outputs_per_period.finish();
+    let len = outputs.len();
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+
+    let rows_len = stats.value_histograms.first().map(|h| h.len()).unwrap_or(0);
+    let rows = (0..rows_len)
+        .map(|r| {
+            let bin = r * VALUE_BINS_PER_ROW;
+            let sat = (bin as f64 / BINS_PER_LN).exp() as u64;
+            // merged like the other counters, so that the columns are the same periods
+            let mut row = Counter::new();
+            for (i, histogram) in stats.value_histograms.iter().enumerate() {
+                row.add(i, histogram[r]);
+            }
+            let (cells, _) = row.finish();
+            (format!("{} sat", sat), cells)
+        })
+        .collect();
+    let heatmap = Chart::heatmap(
+        "Histogram of the output values".to_string(),
+        labels.clone(),
+        rows,
+        "from 1 satoshi to 21M bitcoin, logarithmic",
+    );
+
+    let (dust, _) = stats.dust_outputs.finish();
+    let mut chart_dust = Chart::new(
+        format!("Outputs with value lower than {} satoshi [%]", DUST_VALUE),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "dust range".to_string(),
+        data: perc_100(&dust, &outputs),
+        background_color: vec![Color::Grey],
+        border_color: vec![Color::Grey],
+        fill: false,
+        ..Default::default()
+    };
+    chart_dust.add_dataset(dataset, None);
+
+    let (whale, _) = stats.whale_outputs.finish();
+    let mut chart_whale = Chart::new(
+        format!(
+            "Outputs with value of at least {} bitcoin [‰]",
+            WHALE_VALUE / 100_000_000
+        ),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "whale range".to_string(),
+        data: perc_1000(&whale, &outputs),
+        background_color: vec![Color::Blue],
+        border_color: vec![Color::Blue],
+        fill: false,
+        ..Default::default()
+    };
+    chart_whale.add_dataset(dataset, None);
+
+    Page {
+        title: "Output values".to_string(),
+        description: "Distribution of the values of the outputs over time, dust and whale outputs"
+            .to_string(),
+        permalink: "output-values".to_string(),
+        charts: vec![heatmap, chart_dust, chart_whale],
+        tables: vec![],
+        text: "Every row of the histogram contains the outputs with value from the label up to the label of the next row, a factor of about 1.65. Outputs without value, like OP_RETURN outputs, are not in the histogram but are counted in the dust range".to_string(),
+    }
+}
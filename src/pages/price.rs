@@ -0,0 +1,73 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::pages::{index_block, Page};
+use crate::process::{block_index, Stats, BINS_PER_LN, BINS_PER_ROW, MIN_BIN};
+
+pub fn price(stats: &Stats) -> Page {
+    let (blocks, mul) = stats.blocks_per_period.finish();
+    let len = blocks.len();
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+
+    let mut estimates = vec![vec![]; len];
+    for (height, price) in stats.price_estimates.iter() {
+        if let Some(period) = estimates.get_mut(block_index(*height) / mul) {
+            period.push(*price);
+        }
+    }
+    let medians = estimates
+        .iter_mut()
+        .map(|period| {
+            period.sort_unstable();
+            period.get(period.len() / 2).cloned().unwrap_or(0)
+        })
+        .collect();
+    let mut chart = Chart::new(
+        "Bitcoin price estimated from the output values [$]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    chart.set_log_scale();
+    let dataset = Dataset {
+        label: "median daily estimate".to_string(),
+        data: medians,
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+
+    let rows_len = stats.price_histograms.first().map(|h| h.len()).unwrap_or(0);
+    let rows = (0..rows_len)
+        .map(|r| {
+            let bin = MIN_BIN + r * BINS_PER_ROW;
+            let sat = (bin as f64 / BINS_PER_LN).exp() as u64;
+            let cells = (0..len)
+                .map(|i| {
+                    stats
+                        .price_histograms
+                        .iter()
+                        .skip(i * mul)
+                        .take(mul)
+                        .map(|h| h[r])
+                        .sum()
+                })
+                .collect();
+            (format!("{} sat", sat), cells)
+        })
+        .collect();
+    let heatmap = Chart::heatmap(
+        "Histogram of the output values used in the estimation".to_string(),
+        labels,
+        rows,
+        "from 10k to 100M satoshi, logarithmic",
+    );
+
+    Page {
+        title: "On-chain price".to_string(),
+        description: "Bitcoin price in dollars estimated from the payments of round dollar amounts".to_string(),
+        permalink: "price".to_string(),
+        charts: vec![chart, heatmap],
+        tables: vec![],
+        text: "Every day the histogram of the output values is searched for the price making the most outputs round dollar amounts, like UTXOracle. Values which are round bitcoin amounts are ignored, so the estimate is less reliable when round dollar amounts are also round bitcoin amounts, and prices differing by a factor of 2 or 10 may be confused. Before dollar payments were common the estimate is meaningless".to_string(),
+    }
+}
@@ -0,0 +1,61 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
+use crate::pages::{counter_map_chart, index_block, Page};
+use crate::process::{PubkeyStats, PUBKEY_FORMATS};
+
+pub fn pubkey_formats(pubkey_stats: &PubkeyStats) -> Page {
+    let (formats, mul) = pubkey_stats.formats.finish();
+    let len = formats.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let zeros = vec![0u64; len];
+    let mut total = vec![0u64; len];
+    for format in formats.values() {
+        for (t, v) in total.iter_mut().zip(format.iter()) {
+            *t += v;
+        }
+    }
+
+    let mut chart = Chart::new(
+        "Public keys by format [-]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let mut chart_perc = Chart::new("Public keys by format [%]".to_string(), Kind::Line, labels);
+    chart_perc.set_stacked();
+    let colors = [Color::Blue, Color::Red, Color::Purple, Color::Orange];
+    for (format, color) in PUBKEY_FORMATS.iter().zip(colors.iter()) {
+        let data = formats.get(*format).unwrap_or(&zeros);
+        let dataset = Dataset {
+            label: format.to_string(),
+            data: data.clone(),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+        let dataset = Dataset {
+            label: format.to_string(),
+            data: perc_100(data, &total),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: true,
+            ..Default::default()
+        };
+        chart_perc.add_dataset(dataset, None);
+    }
+
+    let chart_invalid = counter_map_chart(
+        "Elements looking like a public key but not on the curve [-]",
+        &pubkey_stats.invalid,
+    );
+
+    Page {
+        title: "Public key formats".to_string(),
+        description: "Compressed, uncompressed, hybrid and x-only public keys in inputs and outputs".to_string(),
+        permalink: "pubkey-formats".to_string(),
+        charts: vec![chart, chart_perc, chart_invalid],
+        tables: vec![],
+        text: "Every appearance of a public key is counted: in script_sig, witness, redeem and witness scripts, tapscripts and output scripts".to_string(),
+    }
+}
@@ -0,0 +1,82 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
+use crate::pages::{counter_map_chart, index_block, Page, Table};
+use crate::process::PubkeyStats;
+use maud::html;
+
+pub fn pubkey_reuse(pubkey_stats: &PubkeyStats) -> Page {
+    let (first_seen, mul) = pubkey_stats.first_seen.finish();
+    let (reused, _) = pubkey_stats.reused.finish();
+    let labels: Vec<_> = (0..first_seen.len()).map(|i| index_block(i, mul)).collect();
+
+    let mut chart = Chart::new(
+        "Public keys appearing for the first time and reused [-]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    for (label, data, color) in [
+        ("first seen", &first_seen, Color::Green),
+        ("reused", &reused, Color::Red),
+    ]
+    .iter()
+    {
+        let dataset = Dataset {
+            label: label.to_string(),
+            data: data.to_vec(),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+
+    let total: Vec<_> = first_seen
+        .iter()
+        .zip(reused.iter())
+        .map(|(f, r)| f + r)
+        .collect();
+    let mut chart_perc = Chart::new(
+        "Public keys already used in a previous transaction [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "reused".to_string(),
+        data: perc_100(&reused, &total),
+        background_color: vec![Color::Red],
+        border_color: vec![Color::Red],
+        fill: false,
+        ..Default::default()
+    };
+    chart_perc.add_dataset(dataset, None);
+
+    let chart_per_kind = counter_map_chart(
+        "Reused public keys by kind [-]",
+        &pubkey_stats.reused_per_kind,
+    );
+    let chart_cross_kind = counter_map_chart(
+        "Public keys used in a new kind, by previous and new kind [-]",
+        &pubkey_stats.cross_kind,
+    );
+
+    let mut cross_kind: Vec<_> = pubkey_stats.cross_kind.sum_all().into_iter().collect();
+    cross_kind.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let table = Table {
+        title: "Public keys used in a new kind".to_string(),
+        header: vec!["Previous kind → new kind".to_string(), "Keys".to_string()],
+        rows: cross_kind
+            .iter()
+            .map(|(pair, count)| vec![html! { (pair) }, html! { (count) }])
+            .collect(),
+    };
+
+    Page {
+        title: "Public key reuse".to_string(),
+        description: "Public keys appearing in more than one transaction and in different script types".to_string(),
+        permalink: "pubkey-reuse".to_string(),
+        charts: vec![chart, chart_perc, chart_per_kind, chart_cross_kind],
+        tables: vec![table],
+        text: "The kind is the type of the output script containing the key or of the output spent by the input revealing it. Keys are compared by their x coordinate and remembered by a 64 bits hash, the chance of a key wrongly counted as reused is negligible".to_string(),
+    }
+}
@@ -1,6 +1,7 @@
 use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::perc_100;
 use crate::pages::{to_label_map, Page};
-use crate::process::TxStats;
+use crate::process::{TxStats, ROUNDNESS_LEVELS};
 
 pub fn rounded_amount(tx_stats: &TxStats) -> Page {
     let mut charts = vec![];
@@ -9,7 +10,7 @@ pub fn rounded_amount(tx_stats: &TxStats) -> Page {
     let map = to_label_map(&vec, mul);
     let labels: Vec<_> = map.keys().cloned().collect();
 
-    let mut chart = Chart::new("Rounded amount [-]".to_string(), Kind::Line, labels);
+    let mut chart = Chart::new("Rounded amount [-]".to_string(), Kind::Line, labels.clone());
 
     let dataset = Dataset {
         label: "rounded amounts".to_string(),
@@ -23,11 +24,52 @@ pub fn rounded_amount(tx_stats: &TxStats) -> Page {
 
     charts.push(chart);
 
+    let (outputs, _) = tx_stats.total_outputs_per_period.finish();
+    let (levels, _) = tx_stats.roundness_per_period.finish();
+    let zeros = vec![0u64; outputs.len()];
+    let mut chart = Chart::new(
+        "Outputs by roundness of the amount [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    chart.set_stacked();
+    for (level, color) in ROUNDNESS_LEVELS.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: level.to_string(),
+            data: perc_100(levels.get(*level).unwrap_or(&zeros), &outputs),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let (candidates, _) = tx_stats.change_candidates_per_period.finish();
+    let (round_change, _) = tx_stats.round_change_per_period.finish();
+    let mut chart = Chart::new(
+        "Txs with the change detected by round amounts [%]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "change detected".to_string(),
+        data: perc_100(&round_change, &candidates),
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
     Page {
         title: "Rounded amount".to_string(),
-        description: "Charts showing the number of outputs which have a rounded amount as value (multiple of 1000)".to_string(),
+        description: "Charts showing the number of outputs which have a rounded amount as value (multiple of 1000, including zero), by roundness level, and the change detected by round amounts".to_string(),
         permalink: "rounded-amount".to_string(),
         charts,
-        text: "".to_string(),
+        tables: vec![],
+        text: "The roundness level is the roundest amount the value is a multiple of, for example 0.03 bitcoin is at the 0.01 BTC level, outputs without value have no level. When all the spendable outputs of a tx but one have a round amount, the payments are likely the round outputs and the remaining output is the change. Only non-coinbase txs with at least 2 spendable outputs which aren't CoinJoins are considered".to_string(),
     }
 }
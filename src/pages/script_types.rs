@@ -98,6 +98,7 @@ pub fn script_types(script_type: &ScriptType) -> Page {
         description: "Charts showing the script types per month.".to_string(),
         permalink: "script-types".to_string(),
         charts,
+        tables: vec![],
         text: format!(
             "all: {}, p2pkh: {}, p2pk: {}, v0_p2wpkh: {}, v0_p2wsh: {}, p2tr: {}, p2sh: {}, other: {} ",
             script_type.all.sum_all(),
@@ -1,11 +1,11 @@
 use crate::charts::{Chart, Color, Dataset, Kind};
-use crate::pages::{map_by_value, Page};
+use crate::pages::{counter_map_chart, map_by_value, Page};
 use crate::process::ScriptType;
 
 pub fn segwit_multisig(script_type: &ScriptType) -> Page {
     let map = map_by_value(&script_type.multisig);
     let mut chart = Chart::new(
-        "Analyze segwit input scripts counting the occurence of the NofM".to_string(),
+        "Multisig inputs counting the occurence of the NofM".to_string(),
         Kind::Pie,
         map.keys().cloned().collect(),
     );
@@ -19,11 +19,21 @@ pub fn segwit_multisig(script_type: &ScriptType) -> Page {
     };
     chart.add_dataset(dataset, None);
 
+    let chart_wrapping = counter_map_chart(
+        "Multisig inputs by wrapping type [-]",
+        &script_type.multisig_wrapping_per_period,
+    );
+    let chart_m_of_n = counter_map_chart(
+        "Multisig inputs by NofM [-]",
+        &script_type.multisig_per_period,
+    );
+
     Page {
-        title: "Native segwit multisig".to_string(),
-        description: "Analyze segwit input scripts counting the occurence of the NofM".to_string(),
+        title: "Multisig".to_string(),
+        description: "Analyze input scripts spending bare, p2sh, p2sh-p2wsh, p2wsh and taproot multi_a multisig counting the occurence of the NofM".to_string(),
         permalink: "segwit-multisig".to_string(),
-        charts: vec![chart],
+        charts: vec![chart, chart_wrapping, chart_m_of_n],
+        tables: vec![],
         text: "".to_string(),
     }
 }
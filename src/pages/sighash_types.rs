@@ -1,29 +1,101 @@
 use crate::charts::{Chart, Color, Dataset, Kind};
-use crate::pages::{map_by_value, Page};
+use crate::pages::{counter_map_chart, tx_link, Page, Table};
 use crate::process::Stats;
+use maud::html;
+use std::collections::BTreeSet;
 
 pub fn sighash_types(stats: &Stats) -> Page {
-    let map = map_by_value(&stats.sighashtype);
-    let mut chart = Chart::new(
-        "Number of sighash type used".to_string(),
-        Kind::Pie,
-        map.keys().cloned().collect(),
+    let mut chart = counter_map_chart("ECDSA sighash types [-]", &stats.sighash_per_period);
+    chart.set_stacked();
+    chart.set_fill();
+
+    let chart_signatures = counter_map_chart(
+        "ECDSA and Schnorr signatures [-]",
+        &stats.signatures_per_period,
+    );
+    let chart_taproot =
+        counter_map_chart("Taproot sighash types [-]", &stats.tap_sighash_per_period);
+
+    let script_types: Vec<_> = stats.sighash_per_script_type.keys().cloned().collect();
+    let unusual: BTreeSet<_> = stats
+        .sighash_per_script_type
+        .values()
+        .flat_map(|m| m.keys())
+        .filter(|s| *s != "All" && *s != "Default")
+        .collect();
+    let mut chart_script_types = Chart::new(
+        "Signatures with sighash type other than ALL by spent script type [-]".to_string(),
+        Kind::Bar,
+        script_types.clone(),
     );
-    let dataset = Dataset {
-        label: "chart1".to_string(),
-        data: map.values().cloned().collect(),
-        background_color: Color::rainbow(),
-        border_color: vec![],
-        fill: true,
-        ..Default::default()
+    chart_script_types.set_stacked();
+    for (sighash, color) in unusual.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: sighash.to_string(),
+            data: script_types
+                .iter()
+                .map(|t| {
+                    stats.sighash_per_script_type[t]
+                        .get(*sighash)
+                        .cloned()
+                        .unwrap_or(0)
+                })
+                .collect(),
+            background_color: vec![color],
+            border_color: vec![],
+            fill: true,
+            ..Default::default()
+        };
+        chart_script_types.add_dataset(dataset, None);
+    }
+
+    let header = vec![
+        "Height".to_string(),
+        "Transaction".to_string(),
+        "Spent script types".to_string(),
+        "Sighash types".to_string(),
+    ];
+    let first_uses = Table {
+        title: "First use of every sighash type by spent script type".to_string(),
+        header: header.clone(),
+        rows: stats
+            .first_sighash_use
+            .values()
+            .map(|u| {
+                vec![
+                    html! { (u.height) },
+                    tx_link(&u.txid),
+                    html! { (u.script_type) },
+                    html! { (u.sighash) },
+                ]
+            })
+            .collect(),
+    };
+    let last_unusual = Table {
+        title: "Last transactions with a sighash type other than ALL".to_string(),
+        header,
+        rows: stats
+            .unusual_sighash_txs
+            .iter()
+            .rev()
+            .map(|u| {
+                vec![
+                    html! { (u.height) },
+                    tx_link(&u.txid),
+                    html! { (u.script_type) },
+                    html! { (u.sighash) },
+                ]
+            })
+            .collect(),
     };
-    chart.add_dataset(dataset, None);
 
     Page {
         title: "Number of sighash type used".to_string(),
-        description: "Show distribution of sighash type used".to_string(),
+        description: "Show distribution of sighash type used, in ECDSA and taproot signatures"
+            .to_string(),
         permalink: "sighash-types".to_string(),
-        charts: vec![chart],
+        charts: vec![chart, chart_signatures, chart_taproot, chart_script_types],
+        tables: vec![first_uses, last_unusual],
         text: "".to_string(),
     }
 }
@@ -0,0 +1,75 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::{perc_100, perc_1000};
+use crate::pages::{index_block, Page};
+use crate::process::Stats;
+
+pub fn signature_sizes(stats: &Stats) -> Page {
+    let (sizes, mul) = stats.signature_sizes.finish();
+    let len = sizes.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+    let mut total = vec![0u64; len];
+    for size in sizes.values() {
+        for (t, v) in total.iter_mut().zip(size.iter()) {
+            *t += v;
+        }
+    }
+
+    let mut chart = Chart::new(
+        "ECDSA signatures by size, including the sighash byte [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    chart.set_stacked();
+    let zeros = vec![0u64; len];
+    let colors = [Color::Purple, Color::Green, Color::Orange, Color::Red];
+    for (size, color) in ["<71", "71", "72", "73"].iter().zip(colors.iter()) {
+        let dataset = Dataset {
+            label: size.to_string(),
+            data: perc_100(sizes.get(*size).unwrap_or(&zeros), &total),
+            background_color: vec![*color],
+            border_color: vec![*color],
+            fill: true,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+
+    let mut chart_low_r = Chart::new(
+        "ECDSA signatures with low R [%]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "low R".to_string(),
+        data: perc_100(&stats.low_r_signatures.finish().0, &total),
+        background_color: vec![Color::Green],
+        border_color: vec![Color::Green],
+        fill: false,
+        ..Default::default()
+    };
+    chart_low_r.add_dataset(dataset, None);
+
+    let mut chart_high_s = Chart::new(
+        "ECDSA signatures with high S [‰]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let dataset = Dataset {
+        label: "high S".to_string(),
+        data: perc_1000(&stats.high_s_signatures.finish().0, &total),
+        background_color: vec![Color::Red],
+        border_color: vec![Color::Red],
+        fill: false,
+        ..Default::default()
+    };
+    chart_high_s.add_dataset(dataset, None);
+
+    Page {
+        title: "Signature sizes".to_string(),
+        description: "Size of the DER encoded ECDSA signatures, adoption of low R grinding and high S signatures".to_string(),
+        permalink: "signature-sizes".to_string(),
+        charts: vec![chart, chart_low_r, chart_high_s],
+        tables: vec![],
+        text: "Only signatures strictly encoded as required by BIP66 are counted".to_string(),
+    }
+}
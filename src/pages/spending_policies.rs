@@ -0,0 +1,37 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::pages::{counter_map_chart, map_by_value, Page};
+use crate::process::ScriptType;
+
+pub fn spending_policies(script_type: &ScriptType) -> Page {
+    let map = map_by_value(&script_type.policy);
+    let mut chart = Chart::new(
+        "Most common spending policies".to_string(),
+        Kind::Pie,
+        map.keys().cloned().collect(),
+    );
+    let dataset = Dataset {
+        label: "chart1".to_string(),
+        data: map.values().cloned().collect(),
+        background_color: Color::rainbow(),
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+
+    let chart_per_period =
+        counter_map_chart("Spending policies [-]", &script_type.policy_per_period);
+
+    let recognized: u64 = script_type.policy.values().sum();
+    Page {
+        title: "Spending policies".to_string(),
+        description: "Scripts revealed by p2wsh and tapscript inputs lifted to a miniscript-like policy, abstracting keys, hashes and timelock values".to_string(),
+        permalink: "spending-policies".to_string(),
+        charts: vec![chart, chart_per_period],
+        tables: vec![],
+        text: format!(
+            "recognized scripts: {}, not recognized scripts: {}",
+            recognized, script_type.policy_unknown
+        ),
+    }
+}
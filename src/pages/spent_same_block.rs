@@ -1,6 +1,7 @@
 use crate::charts::{Chart, Color, Dataset, Kind};
-use crate::pages::{to_label_map, Page};
+use crate::pages::{counter_map_chart, index_block, to_label_map, tx_link, Page, Table};
 use crate::process::{Stats, TxStats};
+use maud::html;
 
 pub fn spent_same_block(stats: &Stats, tx_stats: &TxStats) -> Page {
     let mut charts = vec![];
@@ -65,13 +66,61 @@ pub fn spent_same_block(stats: &Stats, tx_stats: &TxStats) -> Page {
 
     charts.push(chart);
 
+    let (chained, mul) = stats.chained_txs.finish();
+    let labels: Vec<_> = (0..chained.len()).map(|i| index_block(i, mul)).collect();
+    let mut chart = Chart::new(
+        "Transactions depending on transactions in the same block [-]".to_string(),
+        Kind::Line,
+        labels,
+    );
+    let series = [
+        ("chained txs", chained, Color::Blue),
+        ("packages", stats.packages.finish().0, Color::Green),
+        ("CPFP", stats.cpfp.finish().0, Color::Red),
+    ];
+    for (label, data, color) in series {
+        let dataset = Dataset {
+            label: label.to_string(),
+            data,
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let mut chart = counter_map_chart(
+        "Chained transactions by depth of in-block ancestors [-]",
+        &stats.chain_depth,
+    );
+    chart.set_stacked();
+    chart.set_fill();
+    charts.push(chart);
+
+    let longest_chains = Table {
+        title: "Longest chains of transactions in a block".to_string(),
+        header: vec![
+            "Height".to_string(),
+            "Deepest transaction".to_string(),
+            "Depth".to_string(),
+        ],
+        rows: stats
+            .longest_chains
+            .iter()
+            .map(|c| vec![html! { (c.height) }, tx_link(&c.txid), html! { (c.depth) }])
+            .collect(),
+    };
+
     Page {
         title: "Spent in the same block".to_string(),
         description:
-            "Charts showing the number of output which are spent in the same block they are created"
+            "Charts showing the number of output which are spent in the same block they are created, chains of transactions and CPFP"
                 .to_string(),
         permalink: "spent-same-block".to_string(),
         charts,
-        text: "".to_string(),
+        tables: vec![longest_chains],
+        text: "Packages are groups of transactions connected by spends in the same block. A child paying at least twice the fee rate of one of its parents, paying at least 1 sat/vB, is counted as CPFP. The depth of a transaction is the number of generations of its ancestors in the same block".to_string(),
     }
 }
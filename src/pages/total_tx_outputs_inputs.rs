@@ -74,6 +74,7 @@ pub fn total_tx_outputs_inputs(tx_stats: &TxStats) -> Page {
             .to_string(),
         permalink: "total-tx-outputs-inputs".to_string(),
         charts,
+        tables: vec![],
         text: format!(
             "total tx: {}, total inputs: {}, total outputs: {}, total_spendable_outputs: {}",
             tx_stats.total_tx,
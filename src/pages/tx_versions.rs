@@ -0,0 +1,43 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::pages::{counter_map_chart, Page};
+use crate::process::TxStats;
+
+pub fn tx_versions(tx_stats: &TxStats) -> Page {
+    let chart = counter_map_chart(
+        "Non-coinbase transactions by version [-]",
+        &tx_stats.version_per_period,
+    );
+
+    let chart_structure = counter_map_chart(
+        "Version 3 transactions by relation with other version 3 transactions in the block [-]",
+        &tx_stats.v3_structure,
+    );
+
+    let buckets = ["<200", "200-1000", "1000-10000", "10000+"];
+    let mut chart_vsize = Chart::new(
+        "Version 3 transactions by size [vbytes]".to_string(),
+        Kind::Bar,
+        buckets.iter().map(|b| b.to_string()).collect(),
+    );
+    let dataset = Dataset {
+        label: "transactions".to_string(),
+        data: buckets
+            .iter()
+            .map(|b| tx_stats.v3_vsize.get(*b).cloned().unwrap_or(0))
+            .collect(),
+        background_color: vec![Color::Purple],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart_vsize.add_dataset(dataset, None);
+
+    Page {
+        title: "Transaction versions".to_string(),
+        description: "Transactions by version, adoption of version 3 (TRUC) transactions".to_string(),
+        permalink: "tx-versions".to_string(),
+        charts: vec![chart, chart_structure, chart_vsize],
+        tables: vec![],
+        text: "Version 3 transactions opt in the topologically restricted until confirmation (TRUC) policy of BIP431, made for package relay: an unconfirmed version 3 transaction can have a single unconfirmed child, limited to 1000 vbytes, and is limited to 10000 vbytes. Parents and children are counted when both are confirmed in the same block. Coinbase transactions are excluded, their version is chosen by miners".to_string(),
+    }
+}
@@ -0,0 +1,122 @@
+use crate::charts::{Chart, Color, Dataset, Kind};
+use crate::counter::level;
+use crate::pages::{index_block, Page};
+use crate::process::{UtxoStats, UTXO_SCRIPT_TYPES};
+
+pub fn utxo_set(utxo_stats: &UtxoStats) -> Page {
+    let mut charts = vec![];
+
+    let (created, mul) = utxo_stats.created_per_type.finish();
+    let (spent, _) = utxo_stats.spent_per_type.finish();
+    let len = created.values().next().map(|v| v.len()).unwrap_or(0);
+    let labels: Vec<_> = (0..len).map(|i| index_block(i, mul)).collect();
+
+    let mut chart = Chart::new(
+        "UTXO set by script type [-]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let zeros = vec![0u64; len];
+    for (script_type, color) in UTXO_SCRIPT_TYPES.iter().zip(Color::rainbow()) {
+        let dataset = Dataset {
+            label: script_type.to_string(),
+            data: level(
+                created.get(*script_type).unwrap_or(&zeros),
+                spent.get(*script_type).unwrap_or(&zeros),
+            ),
+            background_color: vec![color],
+            border_color: vec![color],
+            fill: false,
+            ..Default::default()
+        };
+        chart.add_dataset(dataset, None);
+    }
+    charts.push(chart);
+
+    let mut chart = Chart::new(
+        "UTXO set value [bitcoin] and size [MB]".to_string(),
+        Kind::Line,
+        labels.clone(),
+    );
+    let dataset = Dataset {
+        label: "Value [bitcoin]".to_string(),
+        data: level(
+            &utxo_stats.created_value.finish().0,
+            &utxo_stats.spent_value.finish().0,
+        )
+        .iter()
+        .map(|sat| sat / 100_000_000)
+        .collect(),
+        background_color: vec![Color::Orange],
+        border_color: vec![Color::Orange],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    let dataset = Dataset {
+        label: "Size [MB]".to_string(),
+        data: level(
+            &utxo_stats.created_size.finish().0,
+            &utxo_stats.spent_size.finish().0,
+        )
+        .iter()
+        .map(|e| *e >> 20)
+        .collect(),
+        background_color: vec![Color::Blue],
+        border_color: vec![Color::Blue],
+        border_dash: Some([5, 5]),
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, Some("y2".to_string()));
+    charts.push(chart);
+
+    let mut chart = Chart::new("Dust UTXO [-]".to_string(), Kind::Line, labels);
+    let dataset = Dataset {
+        label: "dust".to_string(),
+        data: level(
+            &utxo_stats.created_dust.finish().0,
+            &utxo_stats.spent_dust.finish().0,
+        ),
+        background_color: vec![Color::Red],
+        border_color: vec![Color::Red],
+        fill: false,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    let mut chart = Chart::new(
+        "UTXO at the tip by amount [satoshi]".to_string(),
+        Kind::Bar,
+        utxo_stats
+            .tip_amounts
+            .keys()
+            .map(|k| k.trim().to_string())
+            .collect(),
+    );
+    let dataset = Dataset {
+        label: "count".to_string(),
+        data: utxo_stats.tip_amounts.values().cloned().collect(),
+        background_color: vec![Color::Purple],
+        border_color: vec![],
+        fill: true,
+        ..Default::default()
+    };
+    chart.add_dataset(dataset, None);
+    charts.push(chart);
+
+    Page {
+        title: "UTXO set".to_string(),
+        description: "Number of unspent outputs by script type, their value, size and dust"
+            .to_string(),
+        permalink: "utxo-set".to_string(),
+        charts,
+        tables: vec![],
+        text: format!(
+            "UTXO at height {}: {}, total value: {} bitcoin",
+            utxo_stats.tip_height,
+            utxo_stats.tip_count,
+            utxo_stats.tip_value / 100_000_000
+        ),
+    }
+}
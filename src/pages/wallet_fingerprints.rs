@@ -0,0 +1,60 @@
+use crate::counter::CounterMap;
+use crate::pages::{counter_map_chart, index_block, Page, Table};
+use crate::process::TxStats;
+use maud::html;
+
+pub fn wallet_fingerprints(tx_stats: &TxStats) -> Page {
+    let mut fingerprints = CounterMap::default();
+    for (index, period) in tx_stats.top_fingerprints.iter().enumerate() {
+        for (fingerprint, count) in period.top.iter() {
+            fingerprints.add(fingerprint, index, *count);
+        }
+    }
+    let chart = counter_map_chart(
+        "Transactions with the most common wallet fingerprints [-]",
+        &fingerprints,
+    );
+
+    // the last period is incomplete
+    let last = tx_stats.top_fingerprints.len().saturating_sub(2);
+    let period = tx_stats
+        .top_fingerprints
+        .get(last)
+        .cloned()
+        .unwrap_or_default();
+    let table = Table {
+        title: format!(
+            "Most common wallet fingerprints in blocks {}",
+            index_block(last, 1).trim()
+        ),
+        header: vec![
+            "Rank".to_string(),
+            "Fingerprint".to_string(),
+            "Transactions".to_string(),
+            "Share".to_string(),
+        ],
+        rows: period
+            .top
+            .iter()
+            .enumerate()
+            .map(|(i, (fingerprint, count))| {
+                let share = *count as f64 * 100.0 / period.txs.max(1) as f64;
+                vec![
+                    html! { ((i + 1).to_string()) },
+                    html! { (fingerprint) },
+                    html! { (count) },
+                    html! { (format!("{:.1}%", share)) },
+                ]
+            })
+            .collect(),
+    };
+
+    Page {
+        title: "Wallet fingerprints".to_string(),
+        description: "Most common combinations of transaction features revealing the wallet software".to_string(),
+        permalink: "wallet-fingerprints".to_string(),
+        charts: vec![chart],
+        tables: vec![table],
+        text: "A fingerprint is made of: version, nLockTime style (afs is anti fee sniping), nSequence of the inputs, BIP69 ordering, script types of inputs and outputs, position of the change guessed as the only output with the script type of the inputs and low R grinding of ECDSA signatures. Only the 20 most common fingerprints of every period of 1000 blocks are kept, so the less common are undercounted in the chart".to_string(),
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const BLOOM_HASHES: u64 = 7;
+
+/// Bloom filter of the elements seen so far. Memory is bounded to the given size, at the cost of
+/// a false positive rate growing with the number of inserted elements: with about 10 bits per
+/// element it's around 1%.
+pub struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    pub fn new(bytes: usize) -> Self {
+        Self {
+            bits: vec![0u64; (bytes / 8).max(1)],
+        }
+    }
+
+    /// insert the element, returning whether it was (probably) already present
+    pub fn insert<T: Hash + ?Sized>(&mut self, element: &T) -> bool {
+        let mut present = true;
+        for (word, mask) in self.positions(element) {
+            let word = &mut self.bits[word];
+            present &= *word & mask != 0;
+            *word |= mask;
+        }
+        present
+    }
+
+    /// whether the element is (probably) present, without inserting it
+    pub fn contains<T: Hash + ?Sized>(&self, element: &T) -> bool {
+        self.positions(element)
+            .all(|(word, mask)| self.bits[word] & mask != 0)
+    }
+
+    /// remove all the elements
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// word index and bit mask of the bits of the element
+    fn positions<T: Hash + ?Sized>(&self, element: &T) -> impl Iterator<Item = (usize, u64)> {
+        let mut hasher = DefaultHasher::new();
+        element.hash(&mut hasher);
+        let h1 = hasher.finish();
+        let h2 = h1.rotate_left(32) | 1;
+        let len = self.bits.len() as u64 * 64;
+        (0..BLOOM_HASHES).map(move |i| {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % len;
+            ((bit / 64) as usize, 1u64 << (bit % 64))
+        })
+    }
+}
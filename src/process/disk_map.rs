@@ -0,0 +1,253 @@
+use crate::process::bloom::BloomFilter;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Records read from disk to look up a key, the sparse index in memory has the first key of
+/// every block
+const BLOCK_RECORDS: usize = 512;
+
+/// Map with keys of `K` bytes and values of `N` bytes, kept in memory up to a given size. Then
+/// the elements are merged in a file on disk sorted by key, read only for the keys that a bloom
+/// filter of the keys on disk doesn't exclude.
+pub struct DiskMap<const K: usize, const N: usize> {
+    /// elements inserted since the last merge on disk
+    recent: HashMap<[u8; K], [u8; N]>,
+    /// keys removed from the file since the last merge
+    removed: HashSet<[u8; K]>,
+    /// number of elements in `recent` and `removed` causing a merge, `None` to keep everything
+    /// in memory
+    max_recent: Option<usize>,
+    /// keys in the file, rebuilt at every merge so that removed keys don't fill it, to avoid
+    /// reading the file for most of the keys not in the map
+    prefilter: BloomFilter,
+    path: PathBuf,
+    /// records sorted by key, every record is the key followed by the value
+    file: Option<File>,
+    /// first key of every [`BLOCK_RECORDS`] records in the file
+    index: Vec<[u8; K]>,
+    /// number of records in the file
+    on_disk: usize,
+    buffer: Vec<u8>,
+}
+
+impl<const K: usize, const N: usize> DiskMap<K, N> {
+    /// With `bytes` the map uses about this memory, half for the recent elements and half for
+    /// the bloom filter, and the records not fitting are written in `path`. Without, everything
+    /// is kept in memory.
+    pub fn new(path: &Path, bytes: Option<usize>) -> Self {
+        // the hash map needs about twice the size of its elements
+        let max_recent = bytes.map(|b| (b / 2 / (2 * (K + N))).max(1));
+        Self {
+            recent: HashMap::new(),
+            removed: HashSet::new(),
+            max_recent,
+            prefilter: BloomFilter::new(bytes.unwrap_or(0) / 2),
+            path: path.to_path_buf(),
+            file: None,
+            index: vec![],
+            on_disk: 0,
+            buffer: vec![],
+        }
+    }
+
+    /// Insert a key not present in the map
+    pub fn insert(&mut self, key: [u8; K], value: [u8; N]) {
+        self.recent.insert(key, value);
+        self.merge_if_full();
+    }
+
+    pub fn get(&mut self, key: &[u8; K]) -> Option<[u8; N]> {
+        if let Some(value) = self.recent.get(key) {
+            return Some(*value);
+        }
+        if self.removed.contains(key) {
+            return None;
+        }
+        self.get_on_disk(key)
+    }
+
+    pub fn remove(&mut self, key: &[u8; K]) -> Option<[u8; N]> {
+        if let Some(value) = self.recent.remove(key) {
+            return Some(value);
+        }
+        if self.removed.contains(key) {
+            return None;
+        }
+        let value = self.get_on_disk(key)?;
+        self.removed.insert(*key);
+        self.merge_if_full();
+        Some(value)
+    }
+
+    /// Number of elements in the map
+    pub fn len(&self) -> usize {
+        self.recent.len() + self.on_disk - self.removed.len()
+    }
+
+    /// Call `f` on every element of the map, in no particular order
+    pub fn for_each(&mut self, mut f: impl FnMut(&[u8; K], &[u8; N])) {
+        for (key, value) in self.recent.iter() {
+            f(key, value);
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.seek(SeekFrom::Start(0)).unwrap();
+            let mut reader = BufReader::new(file);
+            while let Some((key, value)) = read_record::<K, N>(&mut reader) {
+                if !self.removed.contains(&key) && !self.recent.contains_key(&key) {
+                    f(&key, &value);
+                }
+            }
+        }
+    }
+
+    fn get_on_disk(&mut self, key: &[u8; K]) -> Option<[u8; N]> {
+        if self.on_disk == 0 || !self.prefilter.contains(key) {
+            return None;
+        }
+        let block = self
+            .index
+            .partition_point(|first| first <= key)
+            .checked_sub(1)?;
+        let records = BLOCK_RECORDS.min(self.on_disk - block * BLOCK_RECORDS);
+        let file = self
+            .file
+            .as_mut()
+            .expect("file exists when records are on disk");
+        self.buffer.resize(records * (K + N), 0);
+        file.seek(SeekFrom::Start((block * BLOCK_RECORDS * (K + N)) as u64))
+            .unwrap();
+        file.read_exact(&mut self.buffer).unwrap();
+
+        let record_key = |i: usize| &self.buffer[i * (K + N)..i * (K + N) + K];
+        let (mut low, mut high) = (0, records);
+        while low < high {
+            let mid = (low + high) / 2;
+            match record_key(mid).cmp(&key[..]) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => {
+                    let start = mid * (K + N) + K;
+                    return Some(self.buffer[start..start + N].try_into().unwrap());
+                }
+            }
+        }
+        None
+    }
+
+    fn merge_if_full(&mut self) {
+        if let Some(max_recent) = self.max_recent {
+            if self.recent.len() + self.removed.len() >= max_recent {
+                self.merge();
+            }
+        }
+    }
+
+    /// Merge the recent elements with the records on disk in a new sorted file, dropping the
+    /// removed ones
+    fn merge(&mut self) {
+        let mut recent: Vec<_> = self.recent.drain().collect();
+        recent.sort_unstable_by_key(|(key, _)| *key);
+        let removed = std::mem::take(&mut self.removed);
+        let mut old = self.file.take().map(|mut file| {
+            file.seek(SeekFrom::Start(0)).unwrap();
+            BufReader::new(file)
+        });
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path).unwrap());
+        let mut index = vec![];
+        let mut written = 0;
+        let prefilter = &mut self.prefilter;
+        prefilter.clear();
+        let mut write = |key: &[u8; K], value: &[u8; N]| {
+            if written % BLOCK_RECORDS == 0 {
+                index.push(*key);
+            }
+            prefilter.insert(key);
+            writer.write_all(key).unwrap();
+            writer.write_all(value).unwrap();
+            written += 1;
+        };
+        let mut next_old = || loop {
+            let record = old.as_mut().and_then(read_record::<K, N>)?;
+            if !removed.contains(&record.0) {
+                return Some(record);
+            }
+        };
+
+        let mut old_record = next_old();
+        for (key, value) in recent {
+            while let Some((old_key, old_value)) = old_record.filter(|(k, _)| *k <= key) {
+                // an element inserted again after being removed replaces the old one
+                if old_key != key {
+                    write(&old_key, &old_value);
+                }
+                old_record = next_old();
+            }
+            write(&key, &value);
+        }
+        while let Some((old_key, old_value)) = old_record {
+            write(&old_key, &old_value);
+            old_record = next_old();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        fs::rename(&tmp_path, &self.path).unwrap();
+        self.file = Some(File::open(&self.path).unwrap());
+        self.index = index;
+        self.on_disk = written;
+    }
+}
+
+impl<const K: usize, const N: usize> Drop for DiskMap<K, N> {
+    fn drop(&mut self) {
+        if self.file.is_some() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_record<const K: usize, const N: usize>(
+    reader: &mut BufReader<impl Read>,
+) -> Option<([u8; K], [u8; N])> {
+    let mut key = [0u8; K];
+    let mut value = [0u8; N];
+    reader.read_exact(&mut key).ok()?;
+    reader.read_exact(&mut value).ok()?;
+    Some((key, value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::DiskMap;
+
+    #[test]
+    fn test_disk_map() {
+        let path = std::env::temp_dir().join("test_disk_map.bin");
+        // small enough to merge on disk many times
+        let mut map = DiskMap::<4, 4>::new(&path, Some(1024));
+        for i in 0u32..5_000 {
+            map.insert(i.to_be_bytes(), (i * 2).to_le_bytes());
+        }
+        assert_eq!(map.len(), 5_000);
+        for i in (0u32..5_000).step_by(2) {
+            assert_eq!(map.remove(&i.to_be_bytes()), Some((i * 2).to_le_bytes()));
+        }
+        assert_eq!(map.remove(&0u32.to_be_bytes()), None);
+        map.insert(0u32.to_be_bytes(), [1; 4]);
+        assert_eq!(map.len(), 2_501);
+        for i in 1u32..5_000 {
+            let expected = Some((i * 2).to_le_bytes()).filter(|_| i % 2 == 1);
+            assert_eq!(map.get(&i.to_be_bytes()), expected);
+        }
+        assert_eq!(map.get(&0u32.to_be_bytes()), Some([1; 4]));
+
+        let mut count = 0;
+        map.for_each(|_, _| count += 1);
+        assert_eq!(count, 2_501);
+    }
+}
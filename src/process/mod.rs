@@ -1,43 +1,106 @@
+mod bloom;
+mod disk_map;
+mod policy;
+mod price;
 mod process_bip158;
+mod process_nonce;
 mod process_opret;
+mod process_pubkeys;
 mod process_stats;
 mod process_tx;
+mod process_utxo;
+mod seen;
 
+pub use policy::lift_policy;
+pub use price::{BINS_PER_LN, BINS_PER_ROW, MIN_BIN, VALUE_BINS_PER_ROW};
 pub use process_bip158::{Bip158Stats, ProcessBip158Stats};
+pub use process_nonce::{NonceStats, ProcessNonce};
 pub use process_opret::{OpReturnData, ProcessOpRet, ScriptType};
-pub use process_stats::{ProcessStats, Stats};
-pub use process_tx::{ProcessTxStats, TxStats};
+pub use process_pubkeys::{ProcessPubkeys, PubkeyStats, PUBKEY_FORMATS};
+pub use process_stats::{
+    ProcessStats, Stats, DUST_VALUE, FEE_RATE_BUCKETS, FULLNESS_BUCKETS, MAX_BLOCK_WEIGHT,
+    WHALE_VALUE,
+};
+pub use process_tx::{
+    ProcessTxStats, TxStats, COINJOIN_KINDS, LOCKTIME_CLASSES, PAYMENT_CLASSES, ROUNDNESS_LEVELS,
+    SEQUENCE_CLASSES,
+};
+pub use process_utxo::{ProcessUtxo, UtxoStats, AGE_BUCKETS, UTXO_SCRIPT_TYPES};
+
+use process_stats::SignatureHash;
 
 use blocks_iterator::bitcoin::blockdata::opcodes;
 use blocks_iterator::bitcoin::blockdata::script::Instruction;
-use blocks_iterator::bitcoin::{PublicKey, Script, Transaction};
-
-pub fn parse_pubkeys_in_script(script: &Script) -> Vec<PublicKey> {
-    let mut r = vec![];
-    for el in script.instructions() {
-        if let Ok(Instruction::PushBytes(inst)) = el {
-            if let Ok(p) = PublicKey::from_slice(inst.as_bytes()) {
-                r.push(p);
-            }
-        }
+use blocks_iterator::bitcoin::{Script, TapSighashType, TxIn};
+
+/// Format of a serialized public key, recognized by its length and first byte
+pub fn pubkey_format(data: &[u8]) -> Option<&'static str> {
+    match (data.len(), data.first()) {
+        (33, Some(0x02)) | (33, Some(0x03)) => Some("compressed"),
+        (65, Some(0x04)) => Some("uncompressed"),
+        (65, Some(0x06)) | (65, Some(0x07)) => Some("hybrid"),
+        (32, _) => Some("x-only"),
+        _ => None,
     }
-    r
 }
 
-pub fn parse_pubkeys_in_tx(tx: &Transaction) -> Vec<PublicKey> {
-    let mut r = vec![];
-    for input in tx.input.iter() {
-        for witness_el in input.witness.iter() {
-            if let Ok(p) = PublicKey::from_slice(&witness_el) {
-                r.push(p);
+/// Pushes of the script which may be public keys, x-only keys excluded
+fn pushed_pubkeys(script: &Script) -> impl Iterator<Item = &[u8]> {
+    script.instructions().filter_map(|i| match i {
+        Ok(Instruction::PushBytes(data)) if data.len() != 32 => Some(data.as_bytes()),
+        _ => None,
+    })
+}
+
+/// Keys in the tapscript, 32 bytes pushes followed by a signature check
+fn tapscript_pubkeys(tapscript: &Script) -> Vec<&[u8]> {
+    let instructions: Vec<_> = tapscript.instructions().filter_map(|i| i.ok()).collect();
+    instructions
+        .windows(2)
+        .filter_map(|w| match (&w[0], &w[1]) {
+            (Instruction::PushBytes(data), Instruction::Op(op))
+                if data.len() == 32
+                    && (*op == opcodes::all::OP_CHECKSIG
+                        || *op == opcodes::all::OP_CHECKSIGVERIFY
+                        || *op == opcodes::all::OP_CHECKSIGADD) =>
+            {
+                Some(data.as_bytes())
             }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Elements of the input which may be public keys: pushes in the script_sig, witness elements,
+/// pushes in the revealed redeem or witness script and keys in the tapscript
+pub fn input_pubkeys<'a>(input: &'a TxIn, prevout: &Script) -> Vec<&'a [u8]> {
+    let mut result: Vec<_> = pushed_pubkeys(&input.script_sig).collect();
+    if prevout.is_p2tr() {
+        if let Some(tapscript) = input.witness.tapscript() {
+            result.extend(tapscript_pubkeys(tapscript));
         }
-        r.extend(parse_pubkeys_in_script(&input.script_sig));
+        return result;
     }
-    for output in tx.output.iter() {
-        r.extend(parse_pubkeys_in_script(&output.script_pubkey));
+    result.extend(input.witness.iter().filter(|e| e.len() != 32));
+    if prevout.is_p2sh() {
+        if let Some(redeem_script) = input.script_sig.redeem_script() {
+            result.extend(pushed_pubkeys(redeem_script));
+        }
+    }
+    if let Some(witness_script) = revealed_witness_script(input, prevout) {
+        result.extend(pushed_pubkeys(witness_script));
+    }
+    result
+}
+
+/// Elements of the output script which may be public keys: pushes like in P2PK and bare multisig
+/// and the taproot output key
+pub fn output_pubkeys(script: &Script) -> Vec<&[u8]> {
+    if script.is_p2tr() {
+        vec![&script.as_bytes()[2..]]
+    } else {
+        pushed_pubkeys(script).collect()
     }
-    r
 }
 
 pub fn parse_multisig(witness_script: &[u8]) -> Option<String> {
@@ -54,6 +117,122 @@ pub fn parse_multisig(witness_script: &[u8]) -> Option<String> {
     None
 }
 
+/// Parse a taproot `multi_a` tapscript like
+/// `<pk1> OP_CHECKSIG <pk2> OP_CHECKSIGADD ... <pkn> OP_CHECKSIGADD <m> OP_NUMEQUAL`
+/// returning the same key format of [`parse_multisig`]
+pub fn parse_multi_a(tapscript: &Script) -> Option<String> {
+    let mut instructions = tapscript.instructions();
+    let mut n = 0i64;
+    let m = loop {
+        match instructions.next()?.ok()? {
+            Instruction::PushBytes(key) if key.len() == 32 => {
+                let expected = if n == 0 {
+                    opcodes::all::OP_CHECKSIG
+                } else {
+                    opcodes::all::OP_CHECKSIGADD
+                };
+                if instructions.next()?.ok()?.opcode()? != expected {
+                    return None;
+                }
+                n += 1;
+            }
+            threshold => break threshold.script_num()?,
+        }
+    };
+    let op = instructions.next()?.ok()?.opcode()?;
+    if n == 0
+        || m < 1
+        || m > n
+        || (op != opcodes::all::OP_NUMEQUAL && op != opcodes::all::OP_NUMEQUALVERIFY)
+        || instructions.next().is_some()
+    {
+        return None;
+    }
+    Some(format!("{:02}of{:02}", m, n))
+}
+
+/// Detect if the input is spending a multisig, returning how the multisig is wrapped in the
+/// spent output (`bare`, `p2sh`, `p2sh-p2wsh`, `p2wsh` or `p2tr`) and the key of [`parse_multisig`]
+pub fn classify_multisig(input: &TxIn, prevout: &Script) -> Option<(&'static str, String)> {
+    if prevout.is_multisig() {
+        Some(("bare", parse_multisig(prevout.as_bytes())?))
+    } else if prevout.is_p2sh() {
+        let redeem_script = input.script_sig.redeem_script()?;
+        if redeem_script.is_p2wsh() {
+            Some(("p2sh-p2wsh", parse_multisig(input.witness.last()?)?))
+        } else {
+            Some(("p2sh", parse_multisig(redeem_script.as_bytes())?))
+        }
+    } else if prevout.is_p2wsh() {
+        Some(("p2wsh", parse_multisig(input.witness.last()?)?))
+    } else if prevout.is_p2tr() {
+        Some(("p2tr", parse_multi_a(input.witness.tapscript()?)?))
+    } else {
+        None
+    }
+}
+
+/// Return the script revealed when spending a P2WSH, P2SH-P2WSH or a taproot script path
+pub fn revealed_witness_script<'a>(input: &'a TxIn, prevout: &Script) -> Option<&'a Script> {
+    if prevout.is_p2wsh() || (prevout.is_p2sh() && input.script_sig.redeem_script()?.is_p2wsh()) {
+        input.witness.witness_script()
+    } else if prevout.is_p2tr() {
+        input.witness.tapscript()
+    } else {
+        None
+    }
+}
+
+/// Sighash types of the schnorr signatures of an input spending a taproot output, together with
+/// the spending path: `keypath` or `tapscript`. In the script path every stack element of 64 or
+/// 65 bytes is considered a signature.
+pub fn taproot_sighashes(input: &TxIn, prevout: &Script) -> Option<(&'static str, Vec<String>)> {
+    if !prevout.is_p2tr() {
+        return None;
+    }
+    let witness = &input.witness;
+    let elements = witness.len() - witness.taproot_annex().map(|_| 1).unwrap_or(0);
+    let (path, stack) = match elements {
+        0 => return None,
+        1 => ("keypath", 1),
+        n => ("tapscript", n - 2),
+    };
+    let sighashes = witness
+        .iter()
+        .take(stack)
+        .filter_map(|element| match element.len() {
+            64 => Some(format!("{:?}", TapSighashType::Default)),
+            65 => Some(match TapSighashType::from_consensus_u8(element[64]) {
+                Ok(sighash) => format!("{:?}", sighash),
+                Err(_) => "Invalid".to_string(),
+            }),
+            _ => None,
+        })
+        .collect();
+    Some((path, sighashes))
+}
+
+/// Name of the script type, same classification used in the script types page
+pub fn script_type_name(script: &Script) -> &'static str {
+    if script.is_p2pkh() {
+        "p2pkh"
+    } else if script.is_p2pk() {
+        "p2pk"
+    } else if script.is_p2wpkh() {
+        "v0_p2wpkh"
+    } else if script.is_p2wsh() {
+        "v0_p2wsh"
+    } else if script.is_p2sh() {
+        "p2sh"
+    } else if script.is_p2tr() {
+        "p2tr"
+    } else if script.is_op_return() {
+        "op_return"
+    } else {
+        "other"
+    }
+}
+
 pub fn read_pushnum(value: u8) -> Option<u8> {
     if value >= opcodes::all::OP_PUSHNUM_1.to_u8() && value <= opcodes::all::OP_PUSHNUM_16.to_u8() {
         Some(value - opcodes::all::OP_PUSHNUM_1.to_u8() + 1)
@@ -126,3 +305,191 @@ pub fn decompress_amount(x: u64) -> u64 {
 pub fn block_index(height: u32) -> usize {
     return height as usize / 1000;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::process::{
+        classify_multisig, input_pubkeys, output_pubkeys, parse_multi_a, parse_multisig,
+        pubkey_format, taproot_sighashes,
+    };
+    use blocks_iterator::bitcoin::blockdata::opcodes::all::*;
+    use blocks_iterator::bitcoin::script::Builder;
+    use blocks_iterator::bitcoin::script::PushBytes;
+    use blocks_iterator::bitcoin::{ScriptBuf, TxIn, Witness};
+    use std::convert::TryFrom;
+
+    // 2of2 witness script from the old commented tests
+    const MULTISIG: &str = "52210293de2378b245e0c4a8325d2beb2e537041a3b9b12c96052a9f30954700e56ef3210230d013baf38205252c298625a7c7799e1f11a016d3738198410bcf8bcc1fecab52ae";
+
+    fn multi_a(m: i64, n: usize) -> ScriptBuf {
+        let mut builder = Builder::new();
+        for i in 0..n {
+            builder = builder.push_slice([i as u8 + 1; 32]);
+            builder = builder.push_opcode(if i == 0 { OP_CHECKSIG } else { OP_CHECKSIGADD });
+        }
+        builder.push_int(m).push_opcode(OP_NUMEQUAL).into_script()
+    }
+
+    #[test]
+    fn test_parse_multisig() {
+        let script = hex::decode(MULTISIG).unwrap();
+        assert_eq!(Some("02of02".to_string()), parse_multisig(&script));
+    }
+
+    #[test]
+    fn test_parse_multi_a() {
+        assert_eq!(Some("02of03".to_string()), parse_multi_a(&multi_a(2, 3)));
+        assert_eq!(Some("01of01".to_string()), parse_multi_a(&multi_a(1, 1)));
+        assert_eq!(Some("17of20".to_string()), parse_multi_a(&multi_a(17, 20)));
+        assert_eq!(None, parse_multi_a(&multi_a(4, 3)));
+        assert_eq!(None, parse_multi_a(&multi_a(0, 3)));
+        let single = Builder::new()
+            .push_slice([1u8; 32])
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(None, parse_multi_a(&single));
+    }
+
+    #[test]
+    fn test_classify_multisig() {
+        let script = ScriptBuf::from_bytes(hex::decode(MULTISIG).unwrap());
+
+        let bare = TxIn::default();
+        assert_eq!(
+            Some(("bare", "02of02".to_string())),
+            classify_multisig(&bare, &script)
+        );
+
+        let p2sh = TxIn {
+            script_sig: Builder::new()
+                .push_opcode(OP_PUSHBYTES_0)
+                .push_slice([0u8; 71])
+                .push_slice([0u8; 71])
+                .push_slice(<&PushBytes>::try_from(script.as_bytes()).unwrap())
+                .into_script(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(("p2sh", "02of02".to_string())),
+            classify_multisig(&p2sh, &script.to_p2sh())
+        );
+
+        let p2wsh = TxIn {
+            witness: Witness::from_slice(&[vec![], vec![0u8; 71], script.to_bytes()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(("p2wsh", "02of02".to_string())),
+            classify_multisig(&p2wsh, &script.to_p2wsh())
+        );
+
+        let p2sh_p2wsh = TxIn {
+            script_sig: Builder::new()
+                .push_slice(<&PushBytes>::try_from(script.to_p2wsh().as_bytes()).unwrap())
+                .into_script(),
+            ..p2wsh.clone()
+        };
+        assert_eq!(
+            Some(("p2sh-p2wsh", "02of02".to_string())),
+            classify_multisig(&p2sh_p2wsh, &script.to_p2wsh().to_p2sh())
+        );
+        assert_eq!(None, classify_multisig(&p2wsh, &script.to_p2sh()));
+
+        let p2tr = TxIn {
+            witness: Witness::from_slice(&[
+                vec![1u8; 64],
+                vec![2u8; 64],
+                multi_a(2, 2).to_bytes(),
+                vec![0xc0; 33],
+            ]),
+            ..Default::default()
+        };
+        let output_key = ScriptBuf::from_bytes([&[0x51, 0x20][..], &[3u8; 32]].concat());
+        assert_eq!(
+            Some(("p2tr", "02of02".to_string())),
+            classify_multisig(&p2tr, &output_key)
+        );
+    }
+
+    #[test]
+    fn test_taproot_sighashes() {
+        let output_key = ScriptBuf::from_bytes([&[0x51, 0x20][..], &[3u8; 32]].concat());
+        let keypath = |witness: &[Vec<u8>]| TxIn {
+            witness: Witness::from_slice(witness),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some(("keypath", vec!["Default".to_string()])),
+            taproot_sighashes(&keypath(&[vec![1u8; 64]]), &output_key)
+        );
+        let mut sig = vec![1u8; 65];
+        sig[64] = 0x83;
+        assert_eq!(
+            Some(("keypath", vec!["SinglePlusAnyoneCanPay".to_string()])),
+            taproot_sighashes(&keypath(&[sig.clone()]), &output_key)
+        );
+        // annex is skipped
+        assert_eq!(
+            Some(("keypath", vec!["SinglePlusAnyoneCanPay".to_string()])),
+            taproot_sighashes(&keypath(&[sig, vec![0x50, 1]]), &output_key)
+        );
+        sig = vec![1u8; 65];
+        sig[64] = 0x04;
+        assert_eq!(
+            Some(("keypath", vec!["Invalid".to_string()])),
+            taproot_sighashes(&keypath(&[sig]), &output_key)
+        );
+
+        let script_path = keypath(&[
+            vec![1u8; 64],
+            vec![],
+            vec![2u8; 65],
+            multi_a(2, 3).to_bytes(),
+            vec![0xc0; 33],
+        ]);
+        assert_eq!(
+            Some(("tapscript", vec!["Default".to_string(), "None".to_string()])),
+            taproot_sighashes(&script_path, &output_key)
+        );
+        assert_eq!(None, taproot_sighashes(&script_path, &multi_a(2, 3)));
+    }
+
+    #[test]
+    fn test_input_pubkeys() {
+        let script = ScriptBuf::from_bytes(hex::decode(MULTISIG).unwrap());
+        let p2wsh = TxIn {
+            witness: Witness::from_slice(&[vec![], vec![1u8; 71], script.to_bytes()]),
+            ..Default::default()
+        };
+        let keys = input_pubkeys(&p2wsh, &script.to_p2wsh());
+        let formats: Vec<_> = keys.iter().filter_map(|k| pubkey_format(k)).collect();
+        assert_eq!(formats, vec!["compressed", "compressed"]);
+
+        let output_key = ScriptBuf::from_bytes([&[0x51, 0x20][..], &[3u8; 32]].concat());
+        let p2tr = TxIn {
+            witness: Witness::from_slice(&[
+                vec![1u8; 64],
+                vec![2u8; 64],
+                multi_a(2, 2).to_bytes(),
+                vec![0xc0; 33],
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            input_pubkeys(&p2tr, &output_key),
+            vec![&[1u8; 32][..], &[2u8; 32][..]]
+        );
+        assert_eq!(output_pubkeys(&output_key), vec![&[3u8; 32][..]]);
+        // keys of a bare multisig are the same revealed by the p2wsh input
+        let keys: Vec<_> = keys
+            .into_iter()
+            .filter(|k| pubkey_format(k).is_some())
+            .collect();
+        assert_eq!(output_pubkeys(&script), keys);
+
+        assert_eq!(pubkey_format(&[0x06; 65]), Some("hybrid"));
+        assert_eq!(pubkey_format(&[0x05; 65]), None);
+        assert_eq!(pubkey_format(&[0x04; 33]), None);
+    }
+}
@@ -0,0 +1,530 @@
+use blocks_iterator::bitcoin::blockdata::opcodes::all::*;
+use blocks_iterator::bitcoin::blockdata::script::{read_scriptint_non_minimal, Instruction};
+use blocks_iterator::bitcoin::Script;
+use std::fmt::{Display, Formatter};
+use std::mem::take;
+
+/// Spending policy abstracted from keys, hashes and timelock values, so that scripts with the
+/// same structure are counted together
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Policy {
+    Pk,
+    Older,
+    After,
+    Hash(&'static str),
+    Multi(usize, usize),
+    Thresh(usize, Vec<Policy>),
+    And(Vec<Policy>),
+    Or(Vec<Policy>),
+}
+
+impl Policy {
+    /// conjunction of `policies`, flattening nested `and`
+    fn all(policies: Vec<Policy>) -> Option<Policy> {
+        let mut flat = vec![];
+        for policy in policies {
+            match policy {
+                Policy::And(inner) => flat.extend(inner),
+                p => flat.push(p),
+            }
+        }
+        match flat.len() {
+            0 => None,
+            1 => flat.pop(),
+            _ => Some(Policy::And(flat)),
+        }
+    }
+
+    /// disjunction of `a` and `b`, flattening nested `or`
+    fn or(a: Policy, b: Policy) -> Policy {
+        let mut flat = vec![];
+        for policy in [a, b] {
+            match policy {
+                Policy::Or(inner) => flat.extend(inner),
+                p => flat.push(p),
+            }
+        }
+        Policy::Or(flat)
+    }
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sorted = |policies: &[Policy]| {
+            let mut v: Vec<_> = policies.iter().map(|p| p.to_string()).collect();
+            v.sort();
+            v.join(", ")
+        };
+        match self {
+            Policy::Pk => write!(f, "pk"),
+            Policy::Older => write!(f, "older"),
+            Policy::After => write!(f, "after"),
+            Policy::Hash(kind) => write!(f, "{}", kind),
+            Policy::Multi(k, n) => write!(f, "multi({},{})", k, n),
+            Policy::Thresh(k, subs) if subs.iter().all(|p| *p == Policy::Pk) => {
+                write!(f, "multi({},{})", k, subs.len())
+            }
+            Policy::Thresh(k, subs) => write!(f, "thresh({}, {})", k, sorted(subs)),
+            Policy::And(subs) => write!(f, "and({})", sorted(subs)),
+            Policy::Or(subs) => write!(f, "or({})", sorted(subs)),
+        }
+    }
+}
+
+/// Symbolic element of the stack during the script execution
+#[derive(Debug, Clone)]
+enum Item {
+    /// a value coming from the witness
+    Witness,
+    Key,
+    Num(i64),
+    Data(usize),
+    /// the result of a hash opcode applied to a witness element
+    Digest(&'static str),
+    /// partial sum of satisfied policies, as built by `OP_ADD` or `OP_CHECKSIGADD`
+    Sum(Vec<Policy>),
+    Expr(Policy),
+}
+
+#[derive(Default)]
+struct State {
+    stack: Vec<Item>,
+    alt: Vec<Item>,
+    verified: Vec<Policy>,
+}
+
+impl State {
+    fn pop(&mut self) -> Item {
+        self.stack.pop().unwrap_or(Item::Witness)
+    }
+
+    /// push the policy on the stack or, if `verify`, add it to the verified ones
+    fn result(&mut self, policy: Policy, verify: bool) {
+        if verify {
+            self.verified.push(policy);
+        } else {
+            self.stack.push(Item::Expr(policy));
+        }
+    }
+
+    /// return the policy of this branch and whether it leaves its result on the stack
+    fn finish(self) -> Option<(Option<Policy>, bool)> {
+        if !self.alt.is_empty() {
+            return None;
+        }
+        let mut policies = self.verified;
+        let mut on_stack = false;
+        for item in self.stack {
+            match item {
+                Item::Witness => (),
+                Item::Key => {
+                    policies.push(Policy::Pk);
+                    on_stack = true;
+                }
+                Item::Expr(policy) => {
+                    policies.push(policy);
+                    on_stack = true;
+                }
+                _ => return None,
+            }
+        }
+        Some((Policy::all(policies), on_stack))
+    }
+}
+
+/// An `OP_IF`/`OP_NOTIF` being executed
+struct Frame {
+    outer: State,
+    condition: Option<Policy>,
+    notif: bool,
+    first_branch: Option<(Option<Policy>, bool)>,
+}
+
+fn push_item(data: &[u8]) -> Item {
+    match (data.len(), data.first()) {
+        (33, Some(0x02)) | (33, Some(0x03)) | (65, Some(0x04)) => Item::Key,
+        (len, _) if len <= 5 => Item::Num(read_scriptint_non_minimal(data).unwrap_or(0)),
+        (len, _) => Item::Data(len),
+    }
+}
+
+fn is_key(item: &Item) -> bool {
+    matches!(item, Item::Key | Item::Data(32))
+}
+
+/// `OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY` is the key of a pkh
+fn is_pkh(instructions: &[Instruction]) -> bool {
+    match instructions {
+        [Instruction::Op(OP_DUP), Instruction::Op(OP_HASH160), Instruction::PushBytes(hash), Instruction::Op(OP_EQUALVERIFY), ..] => {
+            hash.len() == 20
+        }
+        _ => false,
+    }
+}
+
+/// Lift a revealed witness script or tapscript to a miniscript-like policy shape such as
+/// `or(and(older, pk), pk)`, returns `None` if the script is not recognized.
+///
+/// The script is executed symbolically, so it recognizes miniscript fragments but also common
+/// hand written scripts like HTLCs. Inscription envelopes `OP_FALSE OP_IF ... OP_ENDIF` are skipped.
+pub fn lift_policy(script: &Script) -> Option<String> {
+    let instructions: Vec<_> = script.instructions().collect::<Result<_, _>>().ok()?;
+    let mut state = State::default();
+    let mut frames: Vec<Frame> = vec![];
+    let mut i = 0;
+    while i < instructions.len() {
+        if is_pkh(&instructions[i..]) {
+            state.stack.push(Item::Key);
+            i += 4;
+            continue;
+        }
+        let op = match instructions[i] {
+            Instruction::PushBytes(data) => {
+                state.stack.push(push_item(data.as_bytes()));
+                i += 1;
+                continue;
+            }
+            Instruction::Op(op) => op,
+        };
+        if let Some(num) = instructions[i].script_num() {
+            state.stack.push(Item::Num(num));
+            i += 1;
+            continue;
+        }
+        match op {
+            OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                let policy = match state.pop() {
+                    k if is_key(&k) => Policy::Pk,
+                    // keys chosen in branches are already lifted to pk
+                    Item::Expr(policy) => policy,
+                    _ => return None,
+                };
+                state.result(policy, op == OP_CHECKSIGVERIFY);
+            }
+            OP_CHECKSIGADD => {
+                if !is_key(&state.pop()) {
+                    return None;
+                }
+                let sum = match state.pop() {
+                    Item::Expr(policy) => vec![policy, Policy::Pk],
+                    Item::Sum(mut sum) => {
+                        sum.push(Policy::Pk);
+                        sum
+                    }
+                    _ => return None,
+                };
+                state.stack.push(Item::Sum(sum));
+            }
+            OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                let n = match state.pop() {
+                    Item::Num(n) if n > 0 => n as usize,
+                    _ => return None,
+                };
+                for _ in 0..n {
+                    if !matches!(state.pop(), Item::Key) {
+                        return None;
+                    }
+                }
+                let k = match state.pop() {
+                    Item::Num(k) if k > 0 && k as usize <= n => k as usize,
+                    _ => return None,
+                };
+                state.result(Policy::Multi(k, n), op == OP_CHECKMULTISIGVERIFY);
+            }
+            OP_CSV | OP_CLTV => {
+                if !matches!(state.pop(), Item::Num(_)) {
+                    return None;
+                }
+                let policy = if op == OP_CSV {
+                    Policy::Older
+                } else {
+                    Policy::After
+                };
+                state.result(policy, false);
+            }
+            OP_VERIFY => match state.pop() {
+                Item::Expr(policy) => state.result(policy, true),
+                _ => return None,
+            },
+            OP_EQUAL | OP_EQUALVERIFY | OP_NUMEQUAL | OP_NUMEQUALVERIFY => {
+                let verify = op == OP_EQUALVERIFY || op == OP_NUMEQUALVERIFY;
+                let policy = match (state.pop(), state.pop()) {
+                    (Item::Data(_), Item::Digest(kind)) => Policy::Hash(kind),
+                    (Item::Num(k), Item::Sum(sum)) if k > 0 && k as usize <= sum.len() => {
+                        Policy::Thresh(k as usize, sum)
+                    }
+                    // size check of a witness element, like `OP_SIZE <32> OP_EQUALVERIFY`
+                    (Item::Num(_), Item::Witness) => {
+                        if !verify {
+                            state.stack.push(Item::Witness);
+                        }
+                        i += 1;
+                        continue;
+                    }
+                    _ => return None,
+                };
+                state.result(policy, verify);
+            }
+            OP_SHA256 | OP_HASH256 | OP_RIPEMD160 | OP_HASH160 => {
+                if !matches!(state.pop(), Item::Witness) {
+                    return None;
+                }
+                let kind = match op {
+                    OP_SHA256 => "sha256",
+                    OP_HASH256 => "hash256",
+                    OP_RIPEMD160 => "ripemd160",
+                    _ => "hash160",
+                };
+                state.stack.push(Item::Digest(kind));
+            }
+            OP_BOOLAND | OP_BOOLOR | OP_ADD => match (op, state.pop(), state.pop()) {
+                (OP_BOOLAND, Item::Expr(b), Item::Expr(a)) => {
+                    state.result(Policy::all(vec![a, b])?, false)
+                }
+                (OP_BOOLOR, Item::Expr(b), Item::Expr(a)) => state.result(Policy::or(a, b), false),
+                (OP_ADD, Item::Expr(b), Item::Expr(a)) => state.stack.push(Item::Sum(vec![a, b])),
+                (OP_ADD, Item::Expr(b), Item::Sum(mut sum)) => {
+                    sum.push(b);
+                    state.stack.push(Item::Sum(sum));
+                }
+                _ => return None,
+            },
+            OP_DUP => {
+                let item = state.pop();
+                state.stack.push(item.clone());
+                state.stack.push(item);
+            }
+            OP_SIZE => {
+                let item = state.pop();
+                state.stack.push(item);
+                state.stack.push(Item::Witness);
+            }
+            OP_DROP => {
+                if let Item::Expr(policy) = state.pop() {
+                    state.result(policy, true);
+                }
+            }
+            OP_TOALTSTACK => {
+                let item = state.pop();
+                state.alt.push(item);
+            }
+            OP_FROMALTSTACK => {
+                let item = state.alt.pop()?;
+                state.stack.push(item);
+            }
+            OP_SWAP | OP_IFDUP | OP_0NOTEQUAL | OP_NOP => (),
+            OP_IF | OP_NOTIF => {
+                let condition = match state.pop() {
+                    Item::Witness => None,
+                    Item::Expr(policy) => Some(policy),
+                    Item::Num(0) if op == OP_IF => {
+                        // envelope never executed, skip to the matching OP_ENDIF
+                        let mut depth = 1;
+                        while depth > 0 {
+                            i += 1;
+                            match instructions.get(i)? {
+                                Instruction::Op(OP_IF) | Instruction::Op(OP_NOTIF) => depth += 1,
+                                Instruction::Op(OP_ENDIF) => depth -= 1,
+                                _ => (),
+                            }
+                        }
+                        i += 1;
+                        continue;
+                    }
+                    _ => return None,
+                };
+                frames.push(Frame {
+                    outer: take(&mut state),
+                    condition,
+                    notif: op == OP_NOTIF,
+                    first_branch: None,
+                });
+            }
+            OP_ELSE => {
+                let frame = frames.last_mut()?;
+                if frame.first_branch.is_some() {
+                    return None;
+                }
+                frame.first_branch = Some(take(&mut state).finish()?);
+            }
+            OP_ENDIF => {
+                let frame = frames.pop()?;
+                let last_branch = take(&mut state).finish()?;
+                let (first, second) = match frame.first_branch {
+                    Some(first) => (first, Some(last_branch)),
+                    None => (last_branch, None),
+                };
+                let on_stack = first.1 || second.as_ref().map(|s| s.1).unwrap_or(false);
+                let first = first.0;
+                let second = second.and_then(|s| s.0);
+                let (when_true, when_false) = if frame.notif {
+                    (second, first)
+                } else {
+                    (first, second)
+                };
+                let policy = match frame.condition {
+                    Some(condition) => {
+                        let when_true = match when_true {
+                            Some(policy) => Policy::all(vec![condition, policy])?,
+                            None => condition,
+                        };
+                        match when_false {
+                            Some(policy) => Policy::or(when_true, policy),
+                            None => when_true,
+                        }
+                    }
+                    None => match (when_true, when_false) {
+                        (Some(a), Some(b)) => Policy::or(a, b),
+                        (Some(a), None) | (None, Some(a)) => a,
+                        (None, None) => return None,
+                    },
+                };
+                state = frame.outer;
+                state.result(policy, !on_stack);
+            }
+            _ => return None,
+        }
+        i += 1;
+    }
+    if !frames.is_empty() {
+        return None;
+    }
+    state.finish()?.0.map(|policy| policy.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lift_policy;
+    use blocks_iterator::bitcoin::blockdata::opcodes::all::*;
+    use blocks_iterator::bitcoin::script::Builder;
+    use blocks_iterator::bitcoin::ScriptBuf;
+
+    const KEY: [u8; 33] = [2u8; 33];
+
+    fn lift(script: ScriptBuf) -> Option<String> {
+        lift_policy(&script)
+    }
+
+    #[test]
+    fn test_pk_and_multisig() {
+        let pk = Builder::new()
+            .push_slice(KEY)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(Some("pk".to_string()), lift(pk));
+
+        let multi = Builder::new()
+            .push_int(2)
+            .push_slice(KEY)
+            .push_slice(KEY)
+            .push_slice(KEY)
+            .push_int(3)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(Some("multi(2,3)".to_string()), lift(multi));
+
+        let multi_a = Builder::new()
+            .push_slice([1u8; 32])
+            .push_opcode(OP_CHECKSIG)
+            .push_slice([2u8; 32])
+            .push_opcode(OP_CHECKSIGADD)
+            .push_int(1)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script();
+        assert_eq!(Some("multi(1,2)".to_string()), lift(multi_a));
+    }
+
+    #[test]
+    fn test_timelocks() {
+        // and_v(v:pk(A),older(144))
+        let script = Builder::new()
+            .push_slice(KEY)
+            .push_opcode(OP_CHECKSIGVERIFY)
+            .push_int(144)
+            .push_opcode(OP_CSV)
+            .into_script();
+        assert_eq!(Some("and(older, pk)".to_string()), lift(script));
+
+        // or_d(pk(A),and_v(v:pkh(B),after(1000)))
+        let script = Builder::new()
+            .push_slice(KEY)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_IFDUP)
+            .push_opcode(OP_NOTIF)
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_slice([3u8; 20])
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_CHECKSIGVERIFY)
+            .push_int(1000)
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_ENDIF)
+            .into_script();
+        assert_eq!(Some("or(and(after, pk), pk)".to_string()), lift(script));
+    }
+
+    #[test]
+    fn test_htlc() {
+        // classic hand written HTLC
+        let script = Builder::new()
+            .push_opcode(OP_HASH160)
+            .push_slice([3u8; 20])
+            .push_opcode(OP_EQUAL)
+            .push_opcode(OP_IF)
+            .push_slice(KEY)
+            .push_opcode(OP_ELSE)
+            .push_int(144)
+            .push_opcode(OP_CSV)
+            .push_opcode(OP_DROP)
+            .push_slice(KEY)
+            .push_opcode(OP_ENDIF)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(
+            Some("or(and(hash160, pk), and(older, pk))".to_string()),
+            lift(script)
+        );
+
+        // miniscript hashlock
+        let script = Builder::new()
+            .push_opcode(OP_SIZE)
+            .push_int(32)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_SHA256)
+            .push_slice([3u8; 32])
+            .push_opcode(OP_EQUALVERIFY)
+            .push_slice(KEY)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(Some("and(pk, sha256)".to_string()), lift(script));
+    }
+
+    #[test]
+    fn test_not_recognized() {
+        let script = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice([3u8; 20])
+            .into_script();
+        assert_eq!(None, lift(script));
+
+        let script = Builder::new()
+            .push_slice(KEY)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_IF)
+            .into_script();
+        assert_eq!(None, lift(script));
+    }
+
+    #[test]
+    fn test_envelope() {
+        let script = Builder::new()
+            .push_slice([1u8; 32])
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_PUSHBYTES_0)
+            .push_opcode(OP_IF)
+            .push_slice(b"ord")
+            .push_opcode(OP_ENDIF)
+            .into_script();
+        assert_eq!(Some("pk".to_string()), lift(script));
+    }
+}
@@ -0,0 +1,142 @@
+use std::convert::TryFrom;
+
+/// Bins of the histogram in a unit of the natural logarithm of the value in satoshi
+pub const BINS_PER_LN: f64 = 50.0;
+
+/// Range of the values considered, from 10k satoshi to 1 bitcoin, as histogram bins
+pub const MIN_BIN: usize = 460;
+pub const MAX_BIN: usize = 921;
+
+/// Bins merged in a row of [`crate::process::Stats::price_histograms`]
+pub const BINS_PER_ROW: usize = 5;
+
+/// Bins merged in a row of [`crate::process::Stats::value_histograms`], half a unit of the
+/// natural logarithm of the value
+pub const VALUE_BINS_PER_ROW: usize = 25;
+
+/// Days with less outputs in the range don't have a reliable estimation
+const MIN_OUTPUTS: u64 = 1_000;
+
+/// A bin can't weigh more than this fraction of the outputs in the range, to limit the effect of
+/// a single value used in many outputs
+const MAX_BIN_SHARE: f64 = 0.008;
+
+/// Round amounts in dollars with their weight, the amounts used only in some of the decades
+/// help to distinguish prices differing by a factor of 10
+const USD_AMOUNTS: [(f64, f64); 14] = [
+    (5.0, 1.0),
+    (10.0, 1.0),
+    (15.0, 0.5),
+    (20.0, 1.0),
+    (25.0, 0.5),
+    (30.0, 0.5),
+    (40.0, 0.5),
+    (50.0, 1.0),
+    (100.0, 1.0),
+    (150.0, 0.5),
+    (200.0, 0.5),
+    (300.0, 0.5),
+    (500.0, 0.5),
+    (1000.0, 0.5),
+];
+
+/// Prices searched, in dollars
+const MIN_PRICE: f64 = 0.01;
+const MAX_PRICE: f64 = 1_000_000.0;
+
+pub fn value_bin(sat: u64) -> usize {
+    ((sat as f64).ln() * BINS_PER_LN) as usize
+}
+
+/// Estimate the price in dollars for a bitcoin from the values of the outputs created in a day,
+/// like UTXOracle: many payments are of round dollar amounts, creating peaks in the histogram of
+/// the values at positions depending on the price. `histogram` contains the number of outputs in
+/// every bin, see [`value_bin`]
+pub fn estimate_price(histogram: &[u16]) -> Option<u64> {
+    if histogram.len() <= MAX_BIN {
+        return None;
+    }
+    let mut bins: Vec<f64> = histogram[..=MAX_BIN].iter().map(|v| *v as f64).collect();
+    bins[..MIN_BIN].fill(0.0);
+
+    // round bitcoin amounts are not payments of round dollar amounts
+    for exp in 4..=8 {
+        for mantissa in [1.0, 2.0, 2.5, 3.0, 5.0] {
+            let bin = value_bin((mantissa * 10f64.powi(exp)) as u64);
+            if bin > MIN_BIN && bin < MAX_BIN {
+                bins[bin] = (bins[bin - 1] + bins[bin + 1]) / 2.0;
+            }
+        }
+    }
+
+    let total: f64 = bins.iter().sum();
+    if total < MIN_OUTPUTS as f64 {
+        return None;
+    }
+    for bin in bins.iter_mut() {
+        *bin = (*bin / total).min(MAX_BIN_SHARE);
+    }
+    // a round amount may fall in the next bin because of rounding, spread it on the neighbours
+    let bins: Vec<_> = (0..bins.len())
+        .map(|i| {
+            let prev = bins[i.saturating_sub(1)];
+            let next = bins.get(i + 1).cloned().unwrap_or(0.0);
+            0.25 * prev + 0.5 * bins[i] + 0.25 * next
+        })
+        .collect();
+
+    let sat_per_btc_ln = 100_000_000f64.ln();
+    let min = (MIN_PRICE.ln() * BINS_PER_LN) as i64;
+    let max = (MAX_PRICE.ln() * BINS_PER_LN) as i64;
+    let (best, score) = (min..=max)
+        .map(|price_bin| {
+            let price_ln = price_bin as f64 / BINS_PER_LN;
+            let score: f64 = USD_AMOUNTS
+                .iter()
+                .filter_map(|(usd, weight)| {
+                    let bin = ((usd.ln() + sat_per_btc_ln - price_ln) * BINS_PER_LN) as i64;
+                    let bin = usize::try_from(bin).ok()?;
+                    bins.get(bin).map(|b| b * weight)
+                })
+                .sum();
+            (price_bin, score)
+        })
+        .fold((0, 0.0), |acc, e| if e.1 > acc.1 { e } else { acc });
+    if score == 0.0 {
+        return None;
+    }
+    Some((best as f64 / BINS_PER_LN).exp().round() as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{estimate_price, value_bin};
+
+    /// Histogram of payments of round dollar amounts at the given price, over a background of
+    /// random values
+    fn histogram(price: f64) -> Vec<u16> {
+        let mut histogram = vec![5u16; 1120];
+        for usd in [5.0, 10.0, 20.0, 50.0, 100.0, 15.0, 25.0, 30.0, 40.0, 150.0] {
+            let sat = usd / price * 100_000_000.0;
+            histogram[value_bin(sat as u64)] += 200;
+        }
+        // round bitcoin amounts are ignored
+        histogram[value_bin(1_000_000)] += 5_000;
+        histogram
+    }
+
+    #[test]
+    fn test_estimate_price() {
+        assert_eq!(estimate_price(&[]), None);
+        assert_eq!(estimate_price(&vec![0u16; 1120]), None);
+        for price in [137.0, 3_456.0, 27_182.0] {
+            let estimate = estimate_price(&histogram(price)).unwrap() as f64;
+            assert!(
+                (estimate / price - 1.0).abs() < 0.03,
+                "{} {}",
+                price,
+                estimate
+            );
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use crate::counter::Counter;
-use crate::process::block_index;
+use crate::counter::{Counter, CounterMap};
+use crate::process::seen::SeenSet;
+use crate::process::{block_index, script_type_name};
 use bitcoin::bip158::BlockFilter;
 use bitcoin::ScriptBuf;
 use blocks_iterator::log::{debug, info};
@@ -25,6 +26,9 @@ pub struct ProcessBip158Stats {
     scripts_1m: HashSet<ScriptBuf>,
     scripts_1m_heights: Vec<u32>,
 
+    /// every script received or spent so far, to detect address reuse
+    seen_scripts: SeenSet,
+
     /// cache the value of the BIP158 filter
     cache: Vec<u32>,
     cache_path: PathBuf,
@@ -32,13 +36,23 @@ pub struct ProcessBip158Stats {
 
 pub struct Bip158Stats {
     pub bip158_filter_size_per_period: Counter,
+
+    /// spendable outputs per period by script type
+    pub outputs_per_type: CounterMap,
+    /// spendable outputs per period by script type, paying a script already received or spent
+    pub reused_outputs_per_type: CounterMap,
 }
 
 impl ProcessBip158Stats {
-    pub fn new(receiver: Receiver<Arc<Option<BlockExtra>>>, target_dir: &PathBuf) -> Self {
+    pub fn new(
+        receiver: Receiver<Arc<Option<BlockExtra>>>,
+        target_dir: &PathBuf,
+        seen_scripts_mb: usize,
+    ) -> Self {
         let mut cache_path = target_dir.clone();
         cache_path.push("raw");
         cache_path.push("bip138_size_cache");
+        let seen_scripts_path = cache_path.with_file_name("seen_scripts.bin");
 
         let cache = match File::open(&cache_path) {
             Ok(mut file) => {
@@ -60,6 +74,7 @@ impl ProcessBip158Stats {
             stats: Bip158Stats::new(),
             scripts_1m: HashSet::new(),
             scripts_1m_heights: vec![],
+            seen_scripts: SeenSet::new(&seen_scripts_path, seen_scripts_mb << 20),
         }
     }
 
@@ -75,7 +90,11 @@ impl ProcessBip158Stats {
                 Some(ref block) => {
                     self.process_block(&block);
                     if period.period_elapsed().is_some() {
-                        info!("busy_time:{}", (busy_time / 1_000_000_000));
+                        info!(
+                            "busy_time:{} seen_scripts:{}",
+                            (busy_time / 1_000_000_000),
+                            self.seen_scripts.len()
+                        );
                     }
                 }
                 None => break,
@@ -129,17 +148,32 @@ impl ProcessBip158Stats {
 
         for tx in block.block().txdata.iter() {
             for input in tx.input.iter() {
-                self.add_script(
-                    &block
-                        .outpoint_values()
-                        .get(&input.previous_output)
-                        .unwrap()
-                        .script_pubkey,
-                    block.height(),
-                );
+                let script_pubkey = &block
+                    .outpoint_values()
+                    .get(&input.previous_output)
+                    .unwrap()
+                    .script_pubkey;
+                self.add_script(script_pubkey, block.height());
+                if !tx.is_coinbase() {
+                    self.seen_scripts.insert(script_pubkey.as_bytes(), []);
+                }
             }
             for output in tx.output.iter() {
-                self.add_script(&output.script_pubkey, block.height())
+                self.add_script(&output.script_pubkey, block.height());
+                if output.script_pubkey.is_op_return() {
+                    continue;
+                }
+                let script_type = script_type_name(&output.script_pubkey);
+                self.stats.outputs_per_type.increment(script_type, index);
+                if self
+                    .seen_scripts
+                    .insert(output.script_pubkey.as_bytes(), [])
+                    .is_some()
+                {
+                    self.stats
+                        .reused_outputs_per_type
+                        .increment(script_type, index);
+                }
             }
         }
     }
@@ -157,6 +191,8 @@ impl Bip158Stats {
     fn new() -> Self {
         Self {
             bip158_filter_size_per_period: Counter::new(),
+            outputs_per_type: CounterMap::default(),
+            reused_outputs_per_type: CounterMap::default(),
         }
     }
 }
@@ -0,0 +1,233 @@
+use crate::counter::Counter;
+use crate::process::seen::SeenIndex;
+use crate::process::{block_index, SignatureHash};
+use blocks_iterator::bitcoin::blockdata::script::Instruction;
+use blocks_iterator::bitcoin::Txid;
+use blocks_iterator::log::info;
+use blocks_iterator::BlockExtra;
+use blocks_iterator::PeriodCounter;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Maximum number of R values kept in [`ProcessNonce::reused`], when reached the values reused
+/// less are dropped
+const MAX_REUSED_R: usize = 100_000;
+
+/// Number of signatures kept in [`NonceStats::last_reused`]
+const LAST_REUSED: usize = 100;
+
+/// Number of R values kept in [`NonceStats::most_reused`]
+const MOST_REUSED: usize = 20;
+
+pub struct ProcessNonce {
+    receiver: Receiver<Arc<Option<BlockExtra>>>,
+    pub stats: NonceStats,
+
+    /// every R value seen so far, with the signature using it first, see [`FirstUse`]
+    seen_r: SeenIndex<12>,
+    /// R values seen more than once, with the number of times they have been reused
+    reused: HashMap<[u8; 32], u64>,
+    reused_r_file: BufWriter<File>,
+    nonce_stats_json_file: File,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct NonceStats {
+    /// ECDSA signatures per period
+    pub signatures: Counter,
+    /// ECDSA signatures with an R value already seen, added also when zero so that it has the
+    /// length of `signatures`
+    pub reused_r: Counter,
+    /// last signatures with an R value already seen
+    pub last_reused: VecDeque<ReusedR>,
+    /// R values in hex reused the most, with the number of reuses
+    pub most_reused: Vec<(String, u64)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReusedR {
+    pub height: u32,
+    pub txid: Txid,
+    pub vin: usize,
+    pub r: String,
+    /// the first signature using the R value
+    pub first: FirstUse,
+}
+
+/// Position of a signature in the chain, the txid isn't kept to save space
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct FirstUse {
+    pub height: u32,
+    /// position of the transaction in the block
+    pub tx_position: u16,
+    pub vin: u16,
+}
+
+impl FirstUse {
+    /// The first 4 bytes of R to confirm the match of the hashes, then the position
+    fn to_bytes(self, r: &[u8; 32]) -> [u8; 12] {
+        let mut bytes = [0u8; 12];
+        bytes[..4].copy_from_slice(&r[..4]);
+        bytes[4..8].copy_from_slice(&self.height.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.tx_position.to_be_bytes());
+        bytes[10..].copy_from_slice(&self.vin.to_be_bytes());
+        bytes
+    }
+
+    /// `None` if the bytes are of a different R value with the same hash
+    fn from_bytes(bytes: &[u8; 12], r: &[u8; 32]) -> Option<Self> {
+        if bytes[..4] != r[..4] {
+            return None;
+        }
+        Some(FirstUse {
+            height: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            tx_position: u16::from_be_bytes(bytes[8..10].try_into().unwrap()),
+            vin: u16::from_be_bytes(bytes[10..].try_into().unwrap()),
+        })
+    }
+}
+
+impl ProcessNonce {
+    pub fn new(
+        receiver: Receiver<Arc<Option<BlockExtra>>>,
+        target_dir: &Path,
+        seen_r_mb: usize,
+    ) -> Self {
+        let reused_r_file = BufWriter::new(
+            File::create(format!("{}/raw/reused_r.txt", target_dir.display())).unwrap(),
+        );
+        let nonce_stats_json_file =
+            File::create(format!("{}/raw/nonce_stats.json", target_dir.display())).unwrap();
+        let seen_r_path = target_dir.join("raw").join("seen_r.bin");
+        ProcessNonce {
+            receiver,
+            stats: NonceStats::default(),
+            seen_r: SeenIndex::new(&seen_r_path, seen_r_mb << 20),
+            reused: HashMap::new(),
+            reused_r_file,
+            nonce_stats_json_file,
+        }
+    }
+
+    pub fn start(mut self) -> NonceStats {
+        let mut busy_time = 0u128;
+        let mut now = Instant::now();
+        let mut period = PeriodCounter::new(Duration::from_secs(10));
+        loop {
+            busy_time += now.elapsed().as_nanos();
+            let received = self.receiver.recv().expect("cannot receive fee block");
+            now = Instant::now();
+            match *received {
+                Some(ref block) => {
+                    self.process_block(block);
+                    if period.period_elapsed().is_some() {
+                        info!(
+                            "busy_time:{} reused_r:{}",
+                            (busy_time / 1_000_000_000),
+                            self.reused.len()
+                        );
+                    }
+                }
+                None => break,
+            }
+        }
+
+        let mut most_reused: Vec<_> = self.reused.iter().collect();
+        most_reused.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        self.stats.most_reused = most_reused
+            .into_iter()
+            .take(MOST_REUSED)
+            .map(|(r, count)| (hex::encode(r), *count))
+            .collect();
+
+        let nonce_stats_json = serde_json::to_string(&self.stats).unwrap();
+        self.nonce_stats_json_file
+            .write_all(nonce_stats_json.as_bytes())
+            .unwrap();
+        self.reused_r_file.flush().unwrap();
+
+        busy_time += now.elapsed().as_nanos();
+        info!(
+            "ending nonce processer, busy time: {}s",
+            (busy_time / 1_000_000_000)
+        );
+
+        self.stats
+    }
+
+    fn process_block(&mut self, block_extra: &BlockExtra) {
+        let height = block_extra.height();
+        let index = block_index(height);
+
+        for (tx_position, (txid, tx)) in block_extra.iter_tx().enumerate() {
+            if tx.is_coinbase() {
+                continue;
+            }
+            for (vin, input) in tx.input.iter().enumerate() {
+                let prevout = &block_extra
+                    .outpoint_values()
+                    .get(&input.previous_output)
+                    .expect("prevout not found")
+                    .script_pubkey;
+                let script_sig = input.script_sig.instructions().filter_map(|i| match i {
+                    Ok(Instruction::PushBytes(data)) => Some(data.as_bytes()),
+                    _ => None,
+                });
+                // taproot witness contains schnorr signatures only
+                let witness = input.witness.iter().filter(|_| !prevout.is_p2tr());
+                for element in script_sig.chain(witness) {
+                    if let Ok(sig) = SignatureHash::from_slice(element) {
+                        self.stats.signatures.increment(index);
+                        self.stats.reused_r.add(index, 0);
+                        let current = FirstUse {
+                            height,
+                            tx_position: u16::try_from(tx_position).unwrap_or(u16::MAX),
+                            vin: u16::try_from(vin).unwrap_or(u16::MAX),
+                        };
+                        let first = self.seen_r.insert(&sig.r, current.to_bytes(&sig.r));
+                        if let Some(first) = first.and_then(|f| FirstUse::from_bytes(&f, &sig.r)) {
+                            self.reused_r(height, txid, vin, &sig.r, first);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn reused_r(&mut self, height: u32, txid: &Txid, vin: usize, r: &[u8; 32], first: FirstUse) {
+        let index = block_index(height);
+        self.stats.reused_r.increment(index);
+        let r_hex = hex::encode(r);
+        writeln!(
+            self.reused_r_file,
+            "{} {} {} {} {} {} {}",
+            height, txid, vin, r_hex, first.height, first.tx_position, first.vin
+        )
+        .unwrap();
+
+        if self.stats.last_reused.len() == LAST_REUSED {
+            self.stats.last_reused.pop_front();
+        }
+        self.stats.last_reused.push_back(ReusedR {
+            height,
+            txid: *txid,
+            vin,
+            r: r_hex,
+            first,
+        });
+
+        *self.reused.entry(*r).or_insert(0) += 1;
+        let mut min = 1;
+        while self.reused.len() > MAX_REUSED_R {
+            self.reused.retain(|_, count| *count > min);
+            min += 1;
+        }
+    }
+}
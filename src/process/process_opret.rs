@@ -1,5 +1,5 @@
-use crate::counter::Counter;
-use crate::process::{block_index, parse_multisig, parse_pubkeys_in_tx};
+use crate::counter::{Counter, CounterMap};
+use crate::process::{block_index, classify_multisig, lift_policy, revealed_witness_script};
 use blocks_iterator::bitcoin::Script;
 use blocks_iterator::log::{debug, info};
 use blocks_iterator::BlockExtra;
@@ -27,7 +27,6 @@ pub struct ProcessOpRet {
     pub op_return_data: OpReturnData,
     pub script_type: ScriptType,
     pub opret_json_file: File,
-    pub parse_pubkeys: bool,
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -42,6 +41,8 @@ pub struct OpReturnData {
     pub month_ago: u32,
     pub year_ago: u32,
 
+    /// no more filled since public keys are counted by [`crate::process::ProcessPubkeys`], kept
+    /// for the readers of opret.json
     pub compressed_starts_with: Counter,
     pub uncompressed_starts_with: Counter,
 }
@@ -58,14 +59,19 @@ pub struct ScriptType {
     pub other: Counter,
     pub multisig: HashMap<String, u64>,
     pub multisig_tx: HashMap<String, String>,
+    /// multisig inputs per period by NofM
+    pub multisig_per_period: CounterMap,
+    /// multisig inputs per period by how the script is wrapped (bare, p2sh, p2wsh...)
+    pub multisig_wrapping_per_period: CounterMap,
+    /// policy shapes of the scripts revealed by p2wsh and tapscript inputs
+    pub policy: HashMap<String, u64>,
+    pub policy_per_period: CounterMap,
+    /// number of revealed scripts which couldn't be lifted to a policy
+    pub policy_unknown: u64,
 }
 
 impl ProcessOpRet {
-    pub fn new(
-        receiver: Receiver<Arc<Option<BlockExtra>>>,
-        target_dir: &PathBuf,
-        parse_pubkeys: bool,
-    ) -> ProcessOpRet {
+    pub fn new(receiver: Receiver<Arc<Option<BlockExtra>>>, target_dir: &PathBuf) -> ProcessOpRet {
         let opret_json_file =
             File::create(format!("{}/raw/opret.json", target_dir.display())).unwrap();
         ProcessOpRet {
@@ -73,7 +79,6 @@ impl ProcessOpRet {
             op_return_data: OpReturnData::new(),
             script_type: ScriptType::new(),
             opret_json_file,
-            parse_pubkeys,
         }
     }
 
@@ -129,28 +134,30 @@ impl ProcessOpRet {
                 self.process_output_script(&output.script_pubkey, index);
             }
             for input in tx.input.iter() {
-                if let Some(witness_script) = input.witness.last() {
-                    if let Some(key) = parse_multisig(witness_script) {
-                        if self.script_type.multisig_tx.get(&key).is_none() {
-                            self.script_type
-                                .multisig_tx
-                                .insert(key.clone(), format!("{}", txid));
-                        }
-                        *self.script_type.multisig.entry(key).or_insert(0) += 1;
+                let prevout = &block_extra
+                    .outpoint_values()
+                    .get(&input.previous_output)
+                    .unwrap()
+                    .script_pubkey;
+                if let Some((wrapping, key)) = classify_multisig(input, prevout) {
+                    if self.script_type.multisig_tx.get(&key).is_none() {
+                        self.script_type
+                            .multisig_tx
+                            .insert(key.clone(), format!("{}", txid));
                     }
+                    self.script_type
+                        .multisig_wrapping_per_period
+                        .increment(wrapping, index);
+                    self.script_type.multisig_per_period.increment(&key, index);
+                    *self.script_type.multisig.entry(key).or_insert(0) += 1;
                 }
-            }
-
-            if self.parse_pubkeys {
-                for p in parse_pubkeys_in_tx(tx) {
-                    if p.compressed {
-                        self.op_return_data
-                            .compressed_starts_with
-                            .increment(p.to_bytes()[0] as usize);
-                    } else {
-                        self.op_return_data
-                            .uncompressed_starts_with
-                            .increment(p.to_bytes()[0] as usize);
+                if let Some(script) = revealed_witness_script(input, prevout) {
+                    match lift_policy(script) {
+                        Some(policy) => {
+                            self.script_type.policy_per_period.increment(&policy, index);
+                            *self.script_type.policy.entry(policy).or_insert(0) += 1;
+                        }
+                        None => self.script_type.policy_unknown += 1,
                     }
                 }
             }
@@ -0,0 +1,248 @@
+use crate::counter::{Counter, CounterMap};
+use crate::process::seen::SeenIndex;
+use crate::process::{
+    block_index, classify_multisig, input_pubkeys, output_pubkeys, pubkey_format, script_type_name,
+};
+use bitcoin::secp256k1::{PublicKey, XOnlyPublicKey};
+use blocks_iterator::log::info;
+use blocks_iterator::BlockExtra;
+use blocks_iterator::PeriodCounter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Public key formats, as returned by [`pubkey_format`]
+pub const PUBKEY_FORMATS: [&str; 4] = ["compressed", "uncompressed", "hybrid", "x-only"];
+
+/// Where a key appears: the type of the output script containing it, or of the output spent by
+/// the input revealing it
+pub const PUBKEY_KINDS: [&str; 8] = [
+    "p2pk",
+    "p2pkh",
+    "v0_p2wpkh",
+    "p2sh",
+    "v0_p2wsh",
+    "multisig",
+    "p2tr",
+    "other",
+];
+
+/// Blocks with less candidate keys than this are validated in the processor thread
+const MIN_KEYS_PER_THREAD: usize = 256;
+
+pub struct ProcessPubkeys {
+    receiver: Receiver<Arc<Option<BlockExtra>>>,
+    pub stats: PubkeyStats,
+
+    /// number of threads validating the keys of a block
+    threads: usize,
+    /// x coordinate of the keys seen so far, with the bitset of the indexes in [`PUBKEY_KINDS`]
+    /// of the kinds the key appeared in
+    seen: SeenIndex<1>,
+    pubkey_stats_json_file: File,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct PubkeyStats {
+    /// valid public keys per period by format, every appearance in inputs and outputs is counted
+    pub formats: CounterMap,
+    /// elements looking like a public key by length and first byte, which are not on the curve
+    pub invalid: CounterMap,
+
+    /// keys appearing for the first time, multiple appearances of a key in the same transaction
+    /// and kind count once
+    pub first_seen: Counter,
+    /// keys already appeared in a previous transaction
+    pub reused: Counter,
+    /// reused keys by kind of the new appearance
+    pub reused_per_kind: CounterMap,
+    /// keys appearing in a kind for the first time while already used in another kind, keyed by
+    /// the previous and the new kind
+    pub cross_kind: CounterMap,
+}
+
+impl ProcessPubkeys {
+    pub fn new(
+        receiver: Receiver<Arc<Option<BlockExtra>>>,
+        target_dir: &Path,
+        seen_pubkeys_mb: usize,
+    ) -> Self {
+        let pubkey_stats_json_file =
+            File::create(format!("{}/raw/pubkey_stats.json", target_dir.display())).unwrap();
+        let seen_path = target_dir.join("raw").join("seen_pubkeys.bin");
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        ProcessPubkeys {
+            receiver,
+            stats: PubkeyStats::default(),
+            threads,
+            seen: SeenIndex::new(&seen_path, seen_pubkeys_mb << 20),
+            pubkey_stats_json_file,
+        }
+    }
+
+    pub fn start(mut self) -> PubkeyStats {
+        let mut busy_time = 0u128;
+        let mut now = Instant::now();
+        let mut period = PeriodCounter::new(Duration::from_secs(10));
+        loop {
+            busy_time += now.elapsed().as_nanos();
+            let received = self.receiver.recv().expect("cannot receive fee block");
+            now = Instant::now();
+            match *received {
+                Some(ref block) => {
+                    self.process_block(block);
+                    if period.period_elapsed().is_some() {
+                        info!("busy_time:{}", (busy_time / 1_000_000_000));
+                    }
+                }
+                None => break,
+            }
+        }
+
+        let pubkey_stats_json = serde_json::to_string(&self.stats).unwrap();
+        self.pubkey_stats_json_file
+            .write_all(pubkey_stats_json.as_bytes())
+            .unwrap();
+
+        busy_time += now.elapsed().as_nanos();
+        info!(
+            "ending pubkeys processer, busy time: {}s",
+            (busy_time / 1_000_000_000)
+        );
+
+        self.stats
+    }
+
+    fn process_block(&mut self, block_extra: &BlockExtra) {
+        let index = block_index(block_extra.height());
+
+        // (transaction position, kind, data)
+        let mut candidates = vec![];
+        for (i, (_, tx)) in block_extra.iter_tx().enumerate() {
+            if !tx.is_coinbase() {
+                for input in tx.input.iter() {
+                    let prevout = &block_extra
+                        .outpoint_values()
+                        .get(&input.previous_output)
+                        .expect("prevout not found")
+                        .script_pubkey;
+                    let kind = match classify_multisig(input, prevout) {
+                        Some(_) => "multisig",
+                        None => script_type_name(prevout),
+                    };
+                    candidates.extend(
+                        input_pubkeys(input, prevout)
+                            .into_iter()
+                            .map(|d| (i, kind, d)),
+                    );
+                }
+            }
+            for output in tx.output.iter() {
+                let script = &output.script_pubkey;
+                if script.is_op_return() {
+                    continue;
+                }
+                let kind = if script.is_multisig() {
+                    "multisig"
+                } else {
+                    script_type_name(script)
+                };
+                candidates.extend(output_pubkeys(script).into_iter().map(|d| (i, kind, d)));
+            }
+        }
+        let candidates: Vec<_> = candidates
+            .into_iter()
+            .filter_map(|(i, kind, data)| pubkey_format(data).map(|format| (i, kind, format, data)))
+            .collect();
+
+        // EC validation is the expensive part, split it between threads in big blocks
+        let chunk_size = (candidates.len() / self.threads).max(MIN_KEYS_PER_THREAD);
+        let valid = thread::scope(|s| {
+            let handles: Vec<_> = candidates
+                .chunks(chunk_size)
+                .map(|chunk| s.spawn(move || validate(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("pubkey validation thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let mut current_tx = usize::MAX;
+        let mut tx_keys = HashSet::new();
+        for ((i, kind, format, data), valid) in candidates.into_iter().zip(valid) {
+            if !valid {
+                self.stats.invalid.increment(format, index);
+                continue;
+            }
+            self.stats.formats.increment(format, index);
+            if i != current_tx {
+                current_tx = i;
+                tx_keys.clear();
+            }
+            let x = x_coordinate(data);
+            if tx_keys.insert((x, kind)) {
+                self.track_reuse(x, kind, index);
+            }
+        }
+    }
+
+    fn track_reuse(&mut self, x: &[u8], kind: &str, index: usize) {
+        let kind_index = PUBKEY_KINDS
+            .iter()
+            .position(|k| *k == kind)
+            .unwrap_or(PUBKEY_KINDS.len() - 1);
+        let kind = PUBKEY_KINDS[kind_index];
+        let bit = 1u8 << kind_index;
+        let seen_in = match self.seen.get(x) {
+            Some([seen_in]) => seen_in,
+            None => {
+                self.stats.first_seen.increment(index);
+                self.seen.set(x, [bit]);
+                return;
+            }
+        };
+        self.stats.reused.increment(index);
+        self.stats.reused_per_kind.increment(kind, index);
+        if seen_in & bit == 0 {
+            for (i, previous) in PUBKEY_KINDS.iter().enumerate() {
+                if seen_in & (1 << i) != 0 {
+                    let pair = format!("{} → {}", previous, kind);
+                    self.stats.cross_kind.increment(&pair, index);
+                }
+            }
+            self.seen.set(x, [seen_in | bit]);
+        }
+    }
+}
+
+/// The x coordinate of the key, so that the same key is recognized in every format
+fn x_coordinate(data: &[u8]) -> &[u8] {
+    if data.len() == 32 {
+        data
+    } else {
+        &data[1..33]
+    }
+}
+
+/// Whether the candidates are valid points on the curve
+fn validate(candidates: &[(usize, &str, &str, &[u8])]) -> Vec<bool> {
+    candidates
+        .iter()
+        .map(|(_, _, format, data)| {
+            if *format == "x-only" {
+                XOnlyPublicKey::from_slice(data).is_ok()
+            } else {
+                PublicKey::from_slice(data).is_ok()
+            }
+        })
+        .collect()
+}
@@ -1,13 +1,15 @@
-use crate::counter::Counter;
-use crate::process::block_index;
+use crate::counter::{Counter, CounterMap};
+use crate::process::price::{
+    estimate_price, value_bin, BINS_PER_ROW, MAX_BIN, MIN_BIN, VALUE_BINS_PER_ROW,
+};
+use crate::process::{block_index, script_type_name, taproot_sighashes};
 use bitcoin::hashes::Hash;
 use blocks_iterator::bitcoin::blockdata::script::Instruction;
-use blocks_iterator::bitcoin::consensus::{deserialize, encode, Decodable};
-use blocks_iterator::bitcoin::{BlockHash, EcdsaSighashType, VarInt};
+use blocks_iterator::bitcoin::{BlockHash, EcdsaSighashType, Txid, VarInt};
 use blocks_iterator::log::info;
 use blocks_iterator::{BlockExtra, PeriodCounter};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
@@ -21,10 +23,12 @@ pub struct ProcessStats {
 
     pub sighash_file: File,
     pub fee_file: File,
+    pub fullness_file: File,
     pub blocks_len_file: File,
     pub stats_json_file: File,
     pub varint_file: File,
     pub price_file: File,
+    pub price_estimate_file: File,
 }
 #[derive(Serialize, Deserialize)]
 pub struct Stats {
@@ -38,12 +42,55 @@ pub struct Stats {
     pub total_script_duplicate_same_tx: usize,
 
     pub total_spent_in_block_per_period: Counter,
+    /// txs spending an output of a tx in the same block per period
+    pub chained_txs: Counter,
+    /// chained txs per period by depth, the length of the longest chain of in-block ancestors
+    pub chain_depth: CounterMap,
+    /// groups of txs connected by spends in the same block per period
+    pub packages: Counter,
+    /// txs paying a fee rate at least [`CPFP_RATIO`] times the fee rate of an in-block parent
+    /// paying at least [`CPFP_MIN_PARENT_FEE_RATE`]
+    pub cpfp: Counter,
+    /// blocks containing the longest chains of txs
+    pub longest_chains: Vec<ChainRecord>,
 
     pub block_size_per_period: Counter,
+    pub blocks_per_period: Counter,
+    /// weight units of the blocks per period
+    pub block_weight_per_period: Counter,
+    /// bytes of the blocks per period serialized without witnesses
+    pub stripped_size_per_period: Counter,
+    /// blocks containing only the coinbase per period
+    pub empty_blocks: Counter,
+    /// blocks per period by weight utilization, see [`FULLNESS_BUCKETS`]
+    pub fullness: CounterMap,
+    /// fees of the blocks by weight utilization
+    pub fee_by_fullness: HashMap<String, u64>,
+    /// virtual size of the blocks by weight utilization
+    pub vsize_by_fullness: HashMap<String, u64>,
     pub witness_size_per_period: Counter,
     pub script_sig_size_per_period: Counter,
-    pub sighashtype: HashMap<String, u64>,
+    /// sighash types of the ECDSA signatures per period
+    pub sighash_per_period: CounterMap,
+    /// signatures by script type of the spent output and sighash type, ECDSA and schnorr
+    pub sighash_per_script_type: BTreeMap<String, BTreeMap<String, u64>>,
+    /// first transaction using a sighash type, keyed by script type and sighash type
+    pub first_sighash_use: BTreeMap<String, SighashUse>,
+    /// last transactions using a sighash type different from ALL
+    pub unusual_sighash_txs: VecDeque<SighashUse>,
+    /// signatures per period by kind: `ecdsa`, `schnorr keypath` and `schnorr tapscript`
+    pub signatures_per_period: CounterMap,
+    /// sighash types of the schnorr signatures per period
+    pub tap_sighash_per_period: CounterMap,
+    /// ECDSA signatures by length in bytes, including the sighash byte
+    pub signature_sizes: CounterMap,
+    pub low_r_signatures: Counter,
+    pub high_s_signatures: Counter,
     pub fee_per_period: Counter,
+    /// transactions per period by fee rate bucket, see [`FEE_RATE_BUCKETS`]
+    pub fee_rates: CounterMap,
+    /// highest fee rate in sat/vB of a transaction in the period, not merged like a [`Counter`]
+    pub max_fee_rate: Vec<u64>,
 
     /// number of inputs using witness (number of element > 0) and not using witness
     pub has_witness: HashMap<String, u64>,
@@ -56,6 +103,108 @@ pub struct Stats {
 
     /// (ln price)*100
     pub log_price: Vec<u16>,
+    /// price in dollars estimated at the last block of every day, see [`estimate_price`]
+    pub price_estimates: Vec<(u32, u64)>,
+    /// histograms of the output values used in the price estimation per period, every row merges
+    /// [`BINS_PER_ROW`] bins of [`Stats::log_price`] starting from [`MIN_BIN`]
+    pub price_histograms: Vec<Vec<u64>>,
+
+    /// histograms of the values of the outputs per period, every row merges
+    /// [`VALUE_BINS_PER_ROW`] bins of [`value_bin`], outputs without value are not counted
+    pub value_histograms: Vec<Vec<u64>>,
+    /// outputs per period
+    pub outputs_per_period: Counter,
+    /// outputs per period with a value lower than [`DUST_VALUE`]
+    pub dust_outputs: Counter,
+    /// outputs per period with a value of at least [`WHALE_VALUE`]
+    pub whale_outputs: Counter,
+}
+
+/// A transaction using a sighash type, `sighash` contains every unusual sighash type used in the
+/// transaction when listed in [`Stats::unusual_sighash_txs`]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SighashUse {
+    pub height: u32,
+    pub txid: Txid,
+    pub script_type: String,
+    pub sighash: String,
+}
+
+/// Maximum weight of a block, in weight units
+pub const MAX_BLOCK_WEIGHT: u64 = 4_000_000;
+
+/// Buckets of block weight utilization, every bucket is a tenth of [`MAX_BLOCK_WEIGHT`]
+pub const FULLNESS_BUCKETS: [&str; 10] = [
+    "0-10%", "10-20%", "20-30%", "30-40%", "40-50%", "50-60%", "60-70%", "70-80%", "80-90%",
+    "90-100%",
+];
+
+pub fn fullness_bucket(weight: u64) -> &'static str {
+    let decile = (weight * 10 / MAX_BLOCK_WEIGHT) as usize;
+    FULLNESS_BUCKETS[decile.min(FULLNESS_BUCKETS.len() - 1)]
+}
+
+/// Outputs with a value lower than this are in the dust range, it's the dust limit of a P2PKH
+/// output
+pub const DUST_VALUE: u64 = 546;
+
+/// Outputs with at least this value, 1000 bitcoin, are in the whale range
+pub const WHALE_VALUE: u64 = 100_000_000_000;
+
+/// Rows of [`Stats::value_histograms`], enough for 21 million bitcoin
+pub const VALUE_ROWS: usize = 71;
+
+/// The deepest tx of a chain of txs confirmed in the same block
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChainRecord {
+    pub height: u32,
+    pub txid: Txid,
+    /// number of in-block ancestors generations
+    pub depth: u64,
+}
+
+/// A child paying at least this multiple of the fee rate of its parent is considered CPFP
+const CPFP_RATIO: f64 = 2.0;
+
+/// Minimum fee rate in sat/vB of a parent bumped by CPFP, children of zero fee parents, common
+/// before 2013, are paying for themselves
+const CPFP_MIN_PARENT_FEE_RATE: f64 = 1.0;
+
+/// Number of blocks kept in [`Stats::longest_chains`]
+const LONGEST_CHAINS: usize = 20;
+
+/// Number of transactions kept in [`Stats::unusual_sighash_txs`]
+const UNUSUAL_SIGHASH_TXS: usize = 100;
+
+/// Fee rate buckets in sat/vB, every bucket contains fee rates lower than the limit and greater
+/// or equal to the limit of the previous one
+pub const FEE_RATE_BUCKETS: [(u64, &str); 18] = [
+    (1, "<1"),
+    (2, "1-2"),
+    (3, "2-3"),
+    (5, "3-5"),
+    (8, "5-8"),
+    (10, "8-10"),
+    (15, "10-15"),
+    (20, "15-20"),
+    (30, "20-30"),
+    (50, "30-50"),
+    (75, "50-75"),
+    (100, "75-100"),
+    (150, "100-150"),
+    (200, "150-200"),
+    (300, "200-300"),
+    (500, "300-500"),
+    (1_000, "500-1000"),
+    (u64::MAX, "1000+"),
+];
+
+pub fn fee_rate_bucket(fee_rate: f64) -> &'static str {
+    FEE_RATE_BUCKETS
+        .iter()
+        .find(|(limit, _)| fee_rate < *limit as f64)
+        .map(|(_, label)| *label)
+        .unwrap_or("1000+")
 }
 
 impl Default for Stats {
@@ -67,16 +216,44 @@ impl Default for Stats {
             total_spent_in_block: 0,
             total_script_duplicate_same_tx: 0,
             total_spent_in_block_per_period: Counter::default(),
+            chained_txs: Counter::default(),
+            chain_depth: CounterMap::default(),
+            packages: Counter::default(),
+            cpfp: Counter::default(),
+            longest_chains: Vec::default(),
             block_size_per_period: Counter::default(),
+            blocks_per_period: Counter::default(),
+            block_weight_per_period: Counter::default(),
+            stripped_size_per_period: Counter::default(),
+            empty_blocks: Counter::default(),
+            fullness: CounterMap::default(),
+            fee_by_fullness: HashMap::default(),
+            vsize_by_fullness: HashMap::default(),
             witness_size_per_period: Counter::default(),
             script_sig_size_per_period: Counter::default(),
-            sighashtype: HashMap::default(),
+            sighash_per_period: CounterMap::default(),
+            sighash_per_script_type: BTreeMap::default(),
+            first_sighash_use: BTreeMap::default(),
+            unusual_sighash_txs: VecDeque::default(),
+            signatures_per_period: CounterMap::default(),
+            tap_sighash_per_period: CounterMap::default(),
+            signature_sizes: CounterMap::default(),
+            low_r_signatures: Counter::default(),
+            high_s_signatures: Counter::default(),
             fee_per_period: Counter::default(),
+            fee_rates: CounterMap::default(),
+            max_fee_rate: Vec::default(),
             has_witness: HashMap::default(),
             witness_elements: HashMap::default(),
             witness_byte_size: HashMap::default(),
             varint_length: Vec::default(),
             log_price: vec![0u16; 1120], // enough for about 50 BTC. 1120 ~= ln(50BTC)*50.0
+            price_estimates: Vec::default(),
+            price_histograms: Vec::default(),
+            value_histograms: Vec::default(),
+            outputs_per_period: Counter::default(),
+            dust_outputs: Counter::default(),
+            whale_outputs: Counter::default(),
         }
     }
 }
@@ -87,6 +264,8 @@ impl ProcessStats {
         let sighash_file =
             File::create(format!("{}/raw/sighashes.txt", target_dir.display())).unwrap();
         let fee_file = File::create(format!("{}/raw/fee.txt", target_dir.display())).unwrap();
+        let fullness_file =
+            File::create(format!("{}/raw/fullness.txt", target_dir.display())).unwrap();
         let blocks_len_file =
             File::create(format!("{}/raw/blocks_len.txt", target_dir.display())).unwrap();
         let stats_json_file =
@@ -94,15 +273,19 @@ impl ProcessStats {
         let varint_file =
             File::create(format!("{}/raw/varint_file.txt", target_dir.display())).unwrap();
         let price_file = File::create(format!("{}/raw/price.csv", target_dir.display())).unwrap();
+        let price_estimate_file =
+            File::create(format!("{}/raw/price_estimate.csv", target_dir.display())).unwrap();
 
         ProcessStats {
             receiver,
             sighash_file,
             fee_file,
+            fullness_file,
             stats_json_file,
             blocks_len_file,
             varint_file,
             price_file,
+            price_estimate_file,
             stats: Stats::new(),
         }
     }
@@ -154,23 +337,40 @@ impl ProcessStats {
     }
 
     fn process_block(&mut self, block_extra: &BlockExtra) {
-        let index = block_index(block_extra.height());
+        let height = block_extra.height();
+        let index = block_index(height);
 
         self.stats
             .block_size_per_period
             .add(index, block_extra.size() as u64);
+        let block = block_extra.block();
+        let weight = block.weight().to_wu();
+        // weight is 3 times the stripped size plus the total size
+        let stripped_size = (weight - block.total_size() as u64) / 3;
+        self.stats.blocks_per_period.increment(index);
+        self.stats.block_weight_per_period.add(index, weight);
+        self.stats
+            .stripped_size_per_period
+            .add(index, stripped_size);
         let mut fees_from_this_block = vec![];
         let tx_hashes: HashSet<_> = block_extra.iter_tx().map(|e| e.0).collect();
         self.stats
             .count_varint_len(block_extra.block().txdata.len());
-        for tx in block_extra.block().txdata.iter() {
+        for (txid, tx) in block_extra.iter_tx() {
             let mut strange_sighash = vec![];
+            let mut tx_sighashes = vec![];
             let mut count_inputs_in_block = 0;
 
             self.stats.count_varint_len(tx.input.len());
             self.stats.count_varint_len(tx.output.len());
 
             for input in tx.input.iter() {
+                let prevout = &block_extra
+                    .outpoint_values()
+                    .get(&input.previous_output)
+                    .expect("prevout not found")
+                    .script_pubkey;
+                let script_type = script_type_name(prevout);
                 self.stats.count_varint_len(input.script_sig.len());
                 self.stats.count_varint_len(input.witness.len());
 
@@ -182,15 +382,12 @@ impl ProcessStats {
 
                 for instr in input.script_sig.instructions() {
                     if let Ok(Instruction::PushBytes(data)) = instr {
-                        if let Ok(sighash) = deserialize::<SignatureHash>(data.as_bytes()) {
-                            *self
-                                .stats
-                                .sighashtype
-                                .entry(format!("{:?}", sighash.0))
-                                .or_insert(0) += 1;
-                            match sighash.0 {
+                        if let Ok(sig) = SignatureHash::from_slice(data.as_bytes()) {
+                            self.stats.count_signature(&sig, index);
+                            tx_sighashes.push((script_type, format!("{:?}", sig.sighash)));
+                            match sig.sighash {
                                 EcdsaSighashType::All | EcdsaSighashType::AllPlusAnyoneCanPay => (),
-                                _ => strange_sighash.push((sighash.0, input.sequence)),
+                                _ => strange_sighash.push((sig.sighash, input.sequence)),
                             };
                         }
                     }
@@ -209,18 +406,29 @@ impl ProcessStats {
                     ))
                     .or_insert(0) += 1;
 
+                let taproot = taproot_sighashes(input, prevout);
+                if let Some((path, sighashes)) = taproot.as_ref() {
+                    for sighash in sighashes {
+                        self.stats
+                            .signatures_per_period
+                            .increment(&format!("schnorr {}", path), index);
+                        self.stats.tap_sighash_per_period.increment(sighash, index);
+                        tx_sighashes.push((script_type, sighash.clone()));
+                    }
+                }
+
                 for vec in input.witness.iter() {
                     self.stats.count_varint_len(vec.len());
 
-                    if let Ok(sighash) = deserialize::<SignatureHash>(vec) {
-                        *self
-                            .stats
-                            .sighashtype
-                            .entry(format!("{:?}", sighash.0))
-                            .or_insert(0) += 1;
-                        match sighash.0 {
+                    if taproot.is_some() {
+                        continue;
+                    }
+                    if let Ok(sig) = SignatureHash::from_slice(vec) {
+                        self.stats.count_signature(&sig, index);
+                        tx_sighashes.push((script_type, format!("{:?}", sig.sighash)));
+                        match sig.sighash {
                             EcdsaSighashType::All | EcdsaSighashType::AllPlusAnyoneCanPay => (),
-                            _ => strange_sighash.push((sighash.0, input.sequence)),
+                            _ => strange_sighash.push((sig.sighash, input.sequence)),
                         };
                     }
                 }
@@ -250,12 +458,18 @@ impl ProcessStats {
 
             if !strange_sighash.is_empty() {
                 self.sighash_file
-                    .write(format!("{} {:?}\n", tx.compute_txid(), strange_sighash).as_bytes())
+                    .write(format!("{} {:?}\n", txid, strange_sighash).as_bytes())
                     .unwrap();
             }
+            self.stats.count_sighash_uses(height, txid, &tx_sighashes);
             if count_inputs_in_block == tx.input.len() {
                 fees_from_this_block.push(block_extra.tx_fee(&tx).unwrap())
             }
+            if !tx.is_coinbase() {
+                let fee = block_extra.tx_fee(tx).expect("prevout not found");
+                let fee_rate = fee as f64 / tx.weight().to_vbytes_ceil() as f64;
+                self.stats.count_fee_rate(fee_rate, index);
+            }
         }
         let tx_len = block_extra.block().txdata.len();
         let tx_with_fee_in_block_len = fees_from_this_block.len();
@@ -284,6 +498,8 @@ impl ProcessStats {
             )
             .unwrap();
 
+        self.process_fullness(block_extra, weight, fee);
+
         let hash = block_extra.block().header.block_hash();
         if self.stats.min_hash > hash {
             self.stats.min_hash = hash;
@@ -301,7 +517,108 @@ impl ProcessStats {
             self.stats.max_tx_per_block = (l, Some(hash));
         }
 
+        self.process_chains(block_extra);
         self.process_price(block_extra);
+        self.process_values(block_extra);
+    }
+
+    fn process_fullness(&mut self, block_extra: &BlockExtra, weight: u64, fee: u64) {
+        let height = block_extra.height();
+        let index = block_index(height);
+        let tx_len = block_extra.block().txdata.len();
+        // added also when not empty, so that it has the length of `blocks_per_period`
+        self.stats.empty_blocks.add(index, (tx_len == 1) as u64);
+        let bucket = fullness_bucket(weight);
+        self.stats.fullness.increment(bucket, index);
+        *self
+            .stats
+            .fee_by_fullness
+            .entry(bucket.to_string())
+            .or_insert(0) += fee;
+        *self
+            .stats
+            .vsize_by_fullness
+            .entry(bucket.to_string())
+            .or_insert(0) += weight.div_ceil(4);
+        writeln!(
+            self.fullness_file,
+            "{},{},{},{:.4},{}",
+            height,
+            tx_len,
+            weight,
+            weight as f64 / MAX_BLOCK_WEIGHT as f64,
+            fee
+        )
+        .unwrap();
+    }
+
+    /// Reconstruct the graph of txs spending outputs of txs in the same block, since txs in a
+    /// block are topologically ordered the parents of a tx are always before it
+    fn process_chains(&mut self, block_extra: &BlockExtra) {
+        let height = block_extra.height();
+        let index = block_index(height);
+        // so that the counters have the length of `packages`, added every block
+        self.stats.chained_txs.add(index, 0);
+        self.stats.cpfp.add(index, 0);
+        let mut positions = HashMap::new();
+        let mut parents: Vec<Vec<usize>> = vec![];
+        let mut depths: Vec<u64> = vec![];
+        let mut fee_rates = vec![];
+        let mut deepest: Option<(u64, &Txid)> = None;
+        for (i, (txid, tx)) in block_extra.iter_tx().enumerate() {
+            positions.insert(*txid, i);
+            let mut tx_parents: Vec<_> = tx
+                .input
+                .iter()
+                .filter_map(|input| positions.get(&input.previous_output.txid).cloned())
+                .collect();
+            tx_parents.sort_unstable();
+            tx_parents.dedup();
+            let fee_rate = if tx.is_coinbase() {
+                0.0
+            } else {
+                let fee = block_extra.tx_fee(tx).expect("prevout not found");
+                fee as f64 / tx.weight().to_vbytes_ceil() as f64
+            };
+            let depth = tx_parents.iter().map(|p| depths[*p] + 1).max().unwrap_or(0);
+            if depth > 0 {
+                self.stats.chained_txs.increment(index);
+                let bucket = match depth {
+                    1 => "1",
+                    2 => "2",
+                    3..=5 => "3-5",
+                    6..=10 => "6-10",
+                    _ => "11+",
+                };
+                self.stats.chain_depth.increment(bucket, index);
+                if tx_parents.iter().any(|p| {
+                    fee_rates[*p] >= CPFP_MIN_PARENT_FEE_RATE
+                        && fee_rate >= CPFP_RATIO * fee_rates[*p]
+                }) {
+                    self.stats.cpfp.increment(index);
+                }
+                if deepest.map(|(d, _)| depth > d).unwrap_or(true) {
+                    deepest = Some((depth, txid));
+                }
+            }
+            parents.push(tx_parents);
+            depths.push(depth);
+            fee_rates.push(fee_rate);
+        }
+        self.stats
+            .packages
+            .add(index, count_packages(&parents) as u64);
+
+        if let Some((depth, txid)) = deepest {
+            let longest = &mut self.stats.longest_chains;
+            longest.push(ChainRecord {
+                height,
+                txid: *txid,
+                depth,
+            });
+            longest.sort_by(|a, b| b.depth.cmp(&a.depth).then(a.height.cmp(&b.height)));
+            longest.truncate(LONGEST_CHAINS);
+        }
     }
 
     fn process_price(&mut self, block_extra: &BlockExtra) {
@@ -316,6 +633,7 @@ impl ProcessStats {
                 + "\n";
 
             self.price_file.write(csv_line.as_bytes()).unwrap();
+            self.process_price_estimate(block_extra.height());
             self.stats.log_price.fill(0);
         }
 
@@ -323,8 +641,7 @@ impl ProcessStats {
             for output in tx.output.iter() {
                 let value = output.value.to_sat();
                 if value > 0 {
-                    let ln_price = (value as f64).ln();
-                    let ln_price_100 = (ln_price * 50.0) as usize;
+                    let ln_price_100 = value_bin(value);
                     if ln_price_100 < self.stats.log_price.len() {
                         self.stats.log_price[ln_price_100] =
                             self.stats.log_price[ln_price_100].saturating_add(1);
@@ -335,6 +652,52 @@ impl ProcessStats {
     }
 }
 
+impl ProcessStats {
+    fn process_values(&mut self, block_extra: &BlockExtra) {
+        let index = block_index(block_extra.height());
+        if self.stats.value_histograms.len() <= index {
+            self.stats
+                .value_histograms
+                .resize(index + 1, vec![0; VALUE_ROWS]);
+        }
+        for tx in block_extra.block().txdata.iter() {
+            for output in tx.output.iter() {
+                let value = output.value.to_sat();
+                // added also when not in range, so that they have the length of `outputs_per_period`
+                self.stats.outputs_per_period.increment(index);
+                self.stats
+                    .dust_outputs
+                    .add(index, (value < DUST_VALUE) as u64);
+                self.stats
+                    .whale_outputs
+                    .add(index, (value >= WHALE_VALUE) as u64);
+                if value > 0 {
+                    let row = (value_bin(value) / VALUE_BINS_PER_ROW).min(VALUE_ROWS - 1);
+                    self.stats.value_histograms[index][row] += 1;
+                }
+            }
+        }
+    }
+
+    fn process_price_estimate(&mut self, height: u32) {
+        if let Some(price) = estimate_price(&self.stats.log_price) {
+            self.stats.price_estimates.push((height, price));
+            writeln!(self.price_estimate_file, "{},{}", height, price).unwrap();
+        }
+
+        let index = block_index(height);
+        let histograms = &mut self.stats.price_histograms;
+        if histograms.len() <= index {
+            let rows = (MAX_BIN - MIN_BIN) / BINS_PER_ROW + 1;
+            histograms.resize(index + 1, vec![0; rows]);
+        }
+        let bins = &self.stats.log_price[MIN_BIN..=MAX_BIN];
+        for (row, values) in histograms[index].iter_mut().zip(bins.chunks(BINS_PER_ROW)) {
+            *row += values.iter().map(|v| *v as u64).sum::<u64>();
+        }
+    }
+}
+
 impl Stats {
     pub fn new() -> Self {
         Stats {
@@ -352,42 +715,265 @@ impl Stats {
         }
     }
 
+    fn count_signature(&mut self, sig: &SignatureHash, index: usize) {
+        self.signatures_per_period.increment("ecdsa", index);
+        self.sighash_per_period
+            .increment(&format!("{:?}", sig.sighash), index);
+        let size = match sig.len {
+            0..=70 => "<71".to_string(),
+            len => len.to_string(),
+        };
+        self.signature_sizes.increment(&size, index);
+        // added also when false, so that the counters have the length of `signature_sizes`
+        self.low_r_signatures.add(index, sig.low_r as u64);
+        self.high_s_signatures.add(index, sig.high_s as u64);
+    }
+
+    /// `sighashes` contains the script type of the spent output and the sighash type of every
+    /// signature in the transaction
+    fn count_sighash_uses(&mut self, height: u32, txid: &Txid, sighashes: &[(&str, String)]) {
+        let mut unusual = vec![];
+        for (script_type, sighash) in sighashes {
+            *self
+                .sighash_per_script_type
+                .entry(script_type.to_string())
+                .or_default()
+                .entry(sighash.clone())
+                .or_insert(0) += 1;
+            self.first_sighash_use
+                .entry(format!("{} {}", script_type, sighash))
+                .or_insert_with(|| SighashUse {
+                    height,
+                    txid: *txid,
+                    script_type: script_type.to_string(),
+                    sighash: sighash.clone(),
+                });
+            if sighash != "All" && sighash != "Default" && !unusual.contains(&sighash) {
+                unusual.push(sighash);
+            }
+        }
+        if !unusual.is_empty() {
+            if self.unusual_sighash_txs.len() == UNUSUAL_SIGHASH_TXS {
+                self.unusual_sighash_txs.pop_front();
+            }
+            let script_types: HashSet<_> = sighashes.iter().map(|(t, _)| *t).collect();
+            let mut script_types: Vec<_> = script_types.into_iter().collect();
+            script_types.sort_unstable();
+            self.unusual_sighash_txs.push_back(SighashUse {
+                height,
+                txid: *txid,
+                script_type: script_types.join(", "),
+                sighash: unusual
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            });
+        }
+    }
+
+    fn count_fee_rate(&mut self, fee_rate: f64, index: usize) {
+        self.fee_rates.increment(fee_rate_bucket(fee_rate), index);
+        if index >= self.max_fee_rate.len() {
+            self.max_fee_rate.resize(index + 1, 0);
+        }
+        let max = &mut self.max_fee_rate[index];
+        *max = (*max).max(fee_rate as u64);
+    }
+
     pub fn count_varint_len(&mut self, len: usize) {
         let this = VarInt(len as u64).size();
         self.varint_length[this] += 1;
     }
 }
 
-struct SignatureHash(pub EcdsaSighashType);
+/// Number of groups of at least 2 txs connected by spends, `parents` contains the positions of
+/// the parents of every tx
+fn count_packages(parents: &[Vec<usize>]) -> usize {
+    fn root(roots: &mut [usize], mut i: usize) -> usize {
+        while roots[i] != i {
+            roots[i] = roots[roots[i]];
+            i = roots[i];
+        }
+        i
+    }
+    let mut roots: Vec<_> = (0..parents.len()).collect();
+    for (i, tx_parents) in parents.iter().enumerate() {
+        for p in tx_parents {
+            let (a, b) = (root(&mut roots, i), root(&mut roots, *p));
+            roots[a] = b;
+        }
+    }
+    let connected: HashSet<_> = (0..parents.len())
+        .filter(|i| !parents[*i].is_empty())
+        .map(|i| root(&mut roots, i))
+        .collect();
+    connected.len()
+}
+
+/// An ECDSA signature followed by the sighash byte
+pub struct SignatureHash {
+    pub sighash: EcdsaSighashType,
+    /// length including the sighash byte
+    pub len: usize,
+    /// R value as a 32 bytes big endian integer
+    pub r: [u8; 32],
+    /// R is lower than 2^255, so that it's encoded in 32 bytes or less
+    pub low_r: bool,
+    /// S is higher than half the curve order, non-standard since BIP146
+    pub high_s: bool,
+}
+
+/// Half the order of the secp256k1 curve
+const HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
 
-impl Decodable for SignatureHash {
-    fn consensus_decode<R: bitcoin::io::Read + ?Sized>(d: &mut R) -> Result<Self, encode::Error> {
-        let first = u8::consensus_decode(d)?;
-        if first != 0x30 {
-            return Err(encode::Error::ParseFailed("Signature must start with 0x30"));
+impl SignatureHash {
+    /// Parse a strict DER signature as defined in BIP66, mirroring `IsValidSignatureEncoding` of
+    /// Bitcoin Core
+    pub fn from_slice(sig: &[u8]) -> Result<Self, &'static str> {
+        if sig.len() < 9 || sig.len() > 73 {
+            return Err("Invalid signature length");
         }
-        let _ = u8::consensus_decode(d)?;
-        let integer_header = u8::consensus_decode(d)?;
-        if integer_header != 0x02 {
-            return Err(encode::Error::ParseFailed("No integer header"));
+        if sig[0] != 0x30 {
+            return Err("Signature must start with 0x30");
         }
-        let length_r = u8::consensus_decode(d)?;
-        for _ in 0..length_r {
-            let _ = u8::consensus_decode(d)?;
+        if sig[1] as usize != sig.len() - 3 {
+            return Err("Invalid sequence length");
         }
-        let integer_header = u8::consensus_decode(d)?;
-        if integer_header != 0x02 {
-            return Err(encode::Error::ParseFailed("No integer header"));
+        let len_r = sig[3] as usize;
+        if 5 + len_r >= sig.len() {
+            return Err("R length out of bounds");
         }
-        let length_s = u8::consensus_decode(d)?;
-        for _ in 0..length_s {
-            let _ = u8::consensus_decode(d)?;
+        let len_s = sig[5 + len_r] as usize;
+        if len_r + len_s + 7 != sig.len() {
+            return Err("Invalid R and S lengths");
         }
+        let r = &sig[4..4 + len_r];
+        let s = &sig[6 + len_r..6 + len_r + len_s];
+        if sig[2] != 0x02 || sig[4 + len_r] != 0x02 {
+            return Err("No integer header");
+        }
+        for integer in [r, s].iter() {
+            if integer.is_empty() {
+                return Err("Zero length integer");
+            }
+            if integer[0] & 0x80 != 0 {
+                return Err("Negative integer");
+            }
+            if integer.len() > 1 && integer[0] == 0x00 && integer[1] & 0x80 == 0 {
+                return Err("Integer with excessive padding");
+            }
+        }
+        let r = &r[r.iter().take_while(|b| **b == 0).count()..];
+        let r = &r[r.len().saturating_sub(32)..];
+        let mut r_value = [0u8; 32];
+        r_value[32 - r.len()..].copy_from_slice(r);
+        let s = &s[s.iter().take_while(|b| **b == 0).count()..];
+        let high_s = s.len() > 32 || (s.len() == 32 && s > &HALF_ORDER[..]);
+
+        Ok(SignatureHash {
+            sighash: EcdsaSighashType::from_consensus(sig[sig.len() - 1] as u32),
+            len: sig.len(),
+            r: r_value,
+            low_r: len_r <= 32,
+            high_s,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{count_packages, SignatureHash};
+    use blocks_iterator::bitcoin::EcdsaSighashType;
 
-        let sighash_u8 = u8::consensus_decode(d)?;
-        let sighash = EcdsaSighashType::from_consensus(sighash_u8 as u32);
+    const SIGNATURE: &str = "3045022100bd3688bbeefe67dbaf34b7e7d250bcbcf99c8a5cf7cb680393f5025b03dac912022057dbf2317c3413b57eeaf712f1599b74213f1a4ea4e3f5091db6f7fe8d02465a01";
+
+    #[test]
+    fn test_decode_signature() {
+        let mut sig = hex::decode(SIGNATURE).unwrap();
+        let parsed = SignatureHash::from_slice(&sig).unwrap();
+        assert_eq!(parsed.sighash, EcdsaSighashType::All);
+        assert_eq!(parsed.len, 72);
+        assert_eq!(&parsed.r[..], &sig[5..37]);
+        assert!(!parsed.low_r);
+        assert!(!parsed.high_s);
 
-        Ok(SignatureHash(sighash))
+        *sig.last_mut().unwrap() = 0x83;
+        let parsed = SignatureHash::from_slice(&sig).unwrap();
+        assert_eq!(parsed.sighash, EcdsaSighashType::SinglePlusAnyoneCanPay);
+
+        // trailing garbage, truncated and wrong sequence length
+        assert!(SignatureHash::from_slice(&[&sig[..], &[0x01]].concat()).is_err());
+        assert!(SignatureHash::from_slice(&sig[..sig.len() - 2]).is_err());
+        let mut wrong = sig.clone();
+        wrong[1] += 1;
+        assert!(SignatureHash::from_slice(&wrong).is_err());
+
+        // R with the 0x00 padding removed becomes negative
+        let negative = [&[0x30, 0x44, 0x02, 0x20][..], &sig[5..]].concat();
+        assert!(SignatureHash::from_slice(&negative).is_err());
+
+        // R with an excessive 0x00 padding
+        let padded = [&[0x30, 0x46, 0x02, 0x22, 0x00][..], &sig[4..]].concat();
+        assert!(SignatureHash::from_slice(&padded).is_err());
+
+        // arbitrary data starting with 0x30
+        assert!(SignatureHash::from_slice(&[0x30; 40]).is_err());
+    }
+
+    #[test]
+    fn test_low_r_high_s() {
+        let sig = hex::decode(SIGNATURE).unwrap();
+        // S = n - S, the high-S version of the same signature
+        let s = &sig[39..71];
+        let n: [u8; 32] = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c,
+            0xd0, 0x36, 0x41, 0x41,
+        ];
+        let mut high_s = [0u8; 32];
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let mut d = n[i] as i16 - s[i] as i16 - borrow;
+            borrow = if d < 0 { 1 } else { 0 };
+            if d < 0 {
+                d += 256;
+            }
+            high_s[i] = d as u8;
+        }
+        let high = [
+            &[0x30, 0x46][..],
+            &sig[2..37],
+            &[0x02, 0x21, 0x00][..],
+            &high_s[..],
+            &[0x01][..],
+        ]
+        .concat();
+        let parsed = SignatureHash::from_slice(&high).unwrap();
+        assert_eq!(parsed.len, 73);
+        assert!(parsed.high_s);
+
+        // R of 32 bytes
+        let low_r = [&[0x30, 0x44, 0x02, 0x20][..], &[0x01; 32][..], &sig[37..]].concat();
+        let parsed = SignatureHash::from_slice(&low_r).unwrap();
+        assert_eq!(parsed.len, 71);
+        assert_eq!(parsed.r, [0x01; 32]);
+        assert!(parsed.low_r);
+        assert!(!parsed.high_s);
+    }
+
+    #[test]
+    fn test_count_packages() {
+        assert_eq!(count_packages(&[]), 0);
+        assert_eq!(count_packages(&[vec![], vec![], vec![]]), 0);
+        assert_eq!(count_packages(&[vec![], vec![0], vec![1]]), 1);
+        assert_eq!(count_packages(&[vec![], vec![], vec![0], vec![1]]), 2);
+        // two chains merged by a tx spending both
+        let parents = [vec![], vec![], vec![0], vec![1], vec![2, 3], vec![]];
+        assert_eq!(count_packages(&parents), 1);
     }
 }
 
@@ -1,13 +1,16 @@
-use crate::counter::Counter;
+use crate::counter::{Counter, CounterMap};
 use crate::pages::bip69::{has_more_than_one_input_output, is_bip69};
-use crate::process::{block_index, compress_amount, encoded_length_7bit_varint};
+use crate::process::{
+    block_index, compress_amount, encoded_length_7bit_varint, script_type_name, SignatureHash,
+};
 use bitcoin::Address;
-use blocks_iterator::bitcoin::{Transaction, Txid, VarInt};
+use blocks_iterator::bitcoin::blockdata::script::Instruction;
+use blocks_iterator::bitcoin::{Script, Sequence, Transaction, Txid, VarInt};
 use blocks_iterator::log::info;
 use blocks_iterator::BlockExtra;
 use blocks_iterator::PeriodCounter;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
@@ -18,6 +21,11 @@ use std::time::{Duration, Instant};
 pub struct ProcessTxStats {
     receiver: Receiver<Arc<Option<BlockExtra>>>,
     pub stats: TxStats,
+
+    /// wallet fingerprints of the txs in the current period, see [`fingerprint`]
+    period_fingerprints: HashMap<String, u64>,
+    /// index of the current period
+    period_index: usize,
     pub tx_stats_json_file: File,
 }
 
@@ -47,11 +55,347 @@ pub struct TxStats {
     pub total_bytes_output_value_compressed_bitcoin_varint: u64,
     pub rounded_amount_per_period: Counter,
     pub rounded_amount: u64,
+    /// outputs per period by roundness level, see [`roundness`]
+    pub roundness_per_period: CounterMap,
+    /// non-coinbase txs per period which aren't CoinJoins with at least 2 spendable outputs
+    pub change_candidates_per_period: Counter,
+    /// txs of [`TxStats::change_candidates_per_period`] with a single non-round output, likely
+    /// the change
+    pub round_change_per_period: Counter,
 
     pub is_bip69: [Counter; 2],
 
     /// number of txs with a nlocktime higher than confirmed block height minus 6
     pub non_deeply_reorgable: Counter,
+
+    /// non-coinbase txs per period by nLockTime class, see [`locktime_class`]
+    pub locktime_per_period: CounterMap,
+
+    /// non-coinbase txs per period by version: `1`, `2`, `3` (TRUC) or `non-standard`
+    pub version_per_period: CounterMap,
+    /// version 3 txs per period by relation with other version 3 txs in the same block:
+    /// `standalone`, `parent`, `child` or `parent and child`
+    pub v3_structure: CounterMap,
+    /// version 3 txs by vsize, bucketed by the TRUC limits of 1000 vB for a child and 10000 vB
+    pub v3_vsize: HashMap<String, u64>,
+
+    /// CoinJoin txs per period by kind, see [`coinjoin_kind`]
+    pub coinjoin_per_period: CounterMap,
+    /// satoshi in the equal-valued outputs of CoinJoin txs per period by kind
+    pub coinjoin_volume: CounterMap,
+    /// estimated participants of CoinJoin txs per period by kind, as the number of equal-valued
+    /// outputs
+    pub coinjoin_participants: CounterMap,
+
+    /// non-coinbase txs per period by payment class, see [`payment_class`]
+    pub payment_class_per_period: CounterMap,
+    /// payments per period, the outputs of a tx excluding a change output, see [`payments`]
+    pub payments_per_period: Counter,
+    /// estimated vbytes saved per period by batched payments compared to a tx for every payment
+    pub batching_saved_vbytes: Counter,
+    /// vbytes of the txs per period
+    pub vsize_per_period: Counter,
+
+    /// most common wallet fingerprints in every period, see [`fingerprint`]
+    pub top_fingerprints: Vec<PeriodFingerprints>,
+
+    /// non-coinbase inputs per period by nSequence class, see [`sequence_class`]
+    pub sequence_per_period: CounterMap,
+    /// non-coinbase txs per period
+    pub non_coinbase_tx_per_period: Counter,
+    /// txs signalling replaceability as defined in BIP125
+    pub rbf_signalling: Counter,
+    /// txs with at least an input enforcing a BIP68 relative locktime
+    pub relative_locktime: Counter,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct PeriodFingerprints {
+    /// non-coinbase txs in the period
+    pub txs: u64,
+    /// most common fingerprints with the number of txs
+    pub top: Vec<(String, u64)>,
+}
+
+/// Number of fingerprints kept for every period in [`TxStats::top_fingerprints`]
+const TOP_FINGERPRINTS: usize = 20;
+
+/// Output values of the Whirlpool pools: 0.001, 0.01, 0.05 and 0.5 bitcoin
+const WHIRLPOOL_POOLS: [u64; 4] = [100_000, 1_000_000, 5_000_000, 50_000_000];
+
+/// Kinds returned by [`coinjoin_kind`]
+pub const COINJOIN_KINDS: [&str; 5] = ["whirlpool", "wasabi 1", "wasabi 2", "joinmarket", "other"];
+
+/// Detect a CoinJoin tx given the number of inputs and the values of the outputs, the largest
+/// group of outputs with the same value is the one of the participants:
+///
+/// * `whirlpool`: 5 inputs and 5 outputs, all with the value of a pool
+/// * `wasabi 2`: at least 50 inputs and 50 outputs, most of the outputs with a value shared with
+///   other outputs, since WabiSabi creates multiple denominations
+/// * `wasabi 1`: at least 10 equal outputs of about 0.1 bitcoin
+/// * `joinmarket`: from 3 to 20 equal outputs, at most one change output for every participant
+///   plus the taker change
+/// * `other`: at least 3 equal outputs
+///
+/// In every case inputs must be at least as many as the equal outputs. Returns the kind with the
+/// value and the number of the equal outputs
+pub fn coinjoin_kind(inputs: usize, values: &[u64]) -> Option<(&'static str, u64, usize)> {
+    let mut groups: HashMap<u64, usize> = HashMap::new();
+    for value in values.iter().filter(|v| **v > 0) {
+        *groups.entry(*value).or_insert(0) += 1;
+    }
+    let (value, equal) = groups
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        .map(|(value, count)| (*value, *count))?;
+    if equal < 3 || inputs < equal {
+        return None;
+    }
+    let outputs = values.len();
+    let kind = if inputs == 5 && outputs == 5 && equal == 5 && WHIRLPOOL_POOLS.contains(&value) {
+        "whirlpool"
+    } else if inputs >= 50
+        && outputs >= 50
+        && groups.values().filter(|c| **c > 1).sum::<usize>() * 2 > outputs
+    {
+        "wasabi 2"
+    } else if equal >= 10 && (8_000_000..=12_000_000).contains(&value) {
+        "wasabi 1"
+    } else if equal <= 20 && outputs <= equal * 2 + 1 {
+        "joinmarket"
+    } else {
+        "other"
+    };
+    Some((kind, value, equal))
+}
+
+/// Virtual size of version, locktime, inputs and outputs count and segwit marker
+const TX_OVERHEAD_VBYTES: u64 = 11;
+
+/// Classes returned by [`payment_class`]
+pub const PAYMENT_CLASSES: [&str; 5] = [
+    "simple payment",
+    "batched payment",
+    "consolidation",
+    "fan-out",
+    "coinjoin",
+];
+
+/// Number of payments of a tx with the given number of spendable outputs, every output but the
+/// change, assumed present when there are at least 2 outputs
+pub fn payments(outputs: usize) -> usize {
+    outputs.saturating_sub(1).max(1)
+}
+
+/// Classify a non-coinbase tx which isn't a CoinJoin by the number of inputs and spendable outputs:
+///
+/// * `consolidation`: at least 3 inputs merged in a single output
+/// * `fan-out`: a single input split in at least 10 outputs
+/// * `batched payment`: at least 2 payments, see [`payments`]
+/// * `simple payment`: a single payment
+pub fn payment_class(inputs: usize, outputs: usize) -> &'static str {
+    if inputs >= 3 && outputs <= 1 {
+        "consolidation"
+    } else if inputs == 1 && outputs >= 10 {
+        "fan-out"
+    } else if payments(outputs) > 1 {
+        "batched payment"
+    } else {
+        "simple payment"
+    }
+}
+
+/// Levels returned by [`roundness`], from the roundest
+pub const ROUNDNESS_LEVELS: [&str; 6] = [
+    "1 BTC",
+    "0.1 BTC",
+    "0.01 BTC",
+    "0.001 BTC",
+    "10k sat",
+    "1k sat",
+];
+
+/// The roundest level the value in satoshi is a multiple of, by the number of trailing decimal
+/// zeros, `None` for values which aren't a multiple of 1000 satoshi or are zero
+pub fn roundness(value: u64) -> Option<&'static str> {
+    if value == 0 {
+        return None;
+    }
+    let mut zeros = 0;
+    let mut value = value;
+    while value.is_multiple_of(10) && zeros < 8 {
+        value /= 10;
+        zeros += 1;
+    }
+    ROUNDNESS_LEVELS.get(8usize.checked_sub(zeros)?).copied()
+}
+
+/// Classes returned by [`locktime_class`]
+pub const LOCKTIME_CLASSES: [&str; 8] = [
+    "zero",
+    "tip height",
+    "tip height - random",
+    "past height",
+    "future height",
+    "past timestamp",
+    "future timestamp",
+    "lightning",
+];
+
+/// Classify the nLockTime of a tx confirmed in a block with the given height and timestamp:
+///
+/// * `tip height`: the height of the previous block, the anti fee sniping of Bitcoin Core when the
+///   tx is confirmed in the next block
+/// * `tip height - random`: less than 100 blocks before the previous block, anti fee sniping
+///   randomly going back in time or confirmed later
+/// * `lightning`: upper byte 0x20, the obscured commitment number of a lightning commitment tx
+/// * `future height` and `future timestamp`: ignored by consensus since every input is final
+pub fn locktime_class(lock_time: u32, height: u32, time: u32) -> &'static str {
+    if lock_time == 0 {
+        "zero"
+    } else if lock_time >> 24 == 0x20 {
+        "lightning"
+    } else if lock_time >= 500_000_000 {
+        if lock_time > time {
+            "future timestamp"
+        } else {
+            "past timestamp"
+        }
+    } else if lock_time >= height {
+        "future height"
+    } else if lock_time + 1 == height {
+        "tip height"
+    } else if lock_time + 100 >= height {
+        "tip height - random"
+    } else {
+        "past height"
+    }
+}
+
+/// Composite fingerprint of the wallet creating a non-coinbase tx, `prevouts` are the scripts of
+/// the spent outputs. Space separated features:
+///
+/// * version
+/// * `lt:` nLockTime style, `afs` is anti fee sniping, see [`locktime_class`]
+/// * `seq:` nSequence class of the inputs or `mixed`, see [`sequence_class`]
+/// * `order:` BIP69 ordering of inputs and outputs, `-` with a single input and output
+/// * `in:` and `out:` script types of inputs and outputs
+/// * `change:` position of the only output with the script type of the inputs, `?` if ambiguous
+/// * `sig:` whether the ECDSA signatures are all with low R, suggesting R grinding
+pub fn fingerprint(tx: &Transaction, prevouts: &[&Script], height: u32, time: u32) -> String {
+    let version = match tx.version.0 {
+        1..=3 => format!("v{}", tx.version.0),
+        _ => "v?".to_string(),
+    };
+    let locktime = match locktime_class(tx.lock_time.to_consensus_u32(), height, time) {
+        "zero" => "zero",
+        "tip height" | "tip height - random" => "afs",
+        "lightning" => "lightning",
+        _ => "other",
+    };
+    let sequences: BTreeSet<_> = tx
+        .input
+        .iter()
+        .map(|i| sequence_class(i.sequence, tx.version.0))
+        .collect();
+    let sequence = match sequences.len() {
+        1 => sequences.iter().next().unwrap().replace(' ', "-"),
+        _ => "mixed".to_string(),
+    };
+    let order = if !has_more_than_one_input_output(tx) {
+        "-"
+    } else if is_bip69(tx) {
+        "bip69"
+    } else {
+        "other"
+    };
+    let inputs: BTreeSet<_> = prevouts.iter().map(|p| script_type_name(p)).collect();
+    let outputs: Vec<_> = tx
+        .output
+        .iter()
+        .map(|o| script_type_name(&o.script_pubkey))
+        .collect();
+    let change: Vec<_> = (0..outputs.len())
+        .filter(|i| inputs.contains(outputs[*i]))
+        .collect();
+    let change = match change.as_slice() {
+        _ if outputs.len() < 2 => "none",
+        [0] => "first",
+        [i] if *i == outputs.len() - 1 => "last",
+        [_] => "middle",
+        _ => "?",
+    };
+    let outputs: BTreeSet<_> = outputs.into_iter().collect();
+
+    let mut signatures = tx
+        .input
+        .iter()
+        .zip(prevouts)
+        .filter(|(_, prevout)| !prevout.is_p2tr())
+        .flat_map(|(input, _)| {
+            let script_sig = input.script_sig.instructions().filter_map(|i| match i {
+                Ok(Instruction::PushBytes(data)) => Some(data.as_bytes()),
+                _ => None,
+            });
+            script_sig.chain(input.witness.iter())
+        })
+        .filter_map(|e| SignatureHash::from_slice(e).ok())
+        .peekable();
+    let signature = if signatures.peek().is_none() {
+        "none"
+    } else if signatures.all(|s| s.low_r) {
+        "lowR"
+    } else {
+        "highR"
+    };
+
+    format!(
+        "{} lt:{} seq:{} order:{} in:{} out:{} change:{} sig:{}",
+        version,
+        locktime,
+        sequence,
+        order,
+        inputs.into_iter().collect::<Vec<_>>().join("+"),
+        outputs.into_iter().collect::<Vec<_>>().join("+"),
+        change,
+        signature
+    )
+}
+
+/// Classes returned by [`sequence_class`]
+pub const SEQUENCE_CLASSES: [&str; 6] = [
+    "final",
+    "locktime",
+    "rbf",
+    "relative height",
+    "relative time",
+    "lightning",
+];
+
+/// Classify the nSequence of an input of a tx with the given version:
+///
+/// * `final`: 0xffffffff, disables nLockTime if every input is final
+/// * `locktime`: 0xfffffffe, enables nLockTime without signalling RBF
+/// * `lightning`: upper byte 0x80, the obscured commitment number of a lightning commitment tx
+/// * `relative height` and `relative time`: BIP68 relative locktime, enforced in version 2 txs
+/// * `rbf`: any other value, signalling RBF without enforcing a relative locktime
+pub fn sequence_class(sequence: Sequence, version: i32) -> &'static str {
+    let value = sequence.to_consensus_u32();
+    if value == 0xffff_ffff {
+        "final"
+    } else if value == 0xffff_fffe {
+        "locktime"
+    } else if value >> 24 == 0x80 {
+        "lightning"
+    } else if version >= 2 && sequence.is_relative_lock_time() {
+        if sequence.is_time_locked() {
+            "relative time"
+        } else {
+            "relative height"
+        }
+    } else {
+        "rbf"
+    }
 }
 
 //TODO split again this one slower together with read
@@ -65,6 +409,8 @@ impl ProcessTxStats {
         ProcessTxStats {
             receiver,
             stats: TxStats::new(),
+            period_fingerprints: HashMap::new(),
+            period_index: 0,
             tx_stats_json_file,
         }
     }
@@ -88,6 +434,8 @@ impl ProcessTxStats {
             }
         }
 
+        self.finish_period();
+
         let tx_stats_json = serde_json::to_string(&self.stats).unwrap();
         self.tx_stats_json_file
             .write_all(tx_stats_json.as_bytes())
@@ -106,11 +454,104 @@ impl ProcessTxStats {
         let index = block_index(block_extra.height());
 
         for (txid, tx) in block_extra.iter_tx() {
-            self.process_tx(*txid, &tx, index, block_extra.height());
+            self.process_tx(
+                *txid,
+                &tx,
+                index,
+                block_extra.height(),
+                block_extra.block().header.time,
+            );
         }
+        self.process_v3(block_extra, index);
+
+        if index != self.period_index {
+            self.finish_period();
+            self.period_index = index;
+        }
+        for (_, tx) in block_extra.iter_tx().filter(|(_, tx)| !tx.is_coinbase()) {
+            let prevouts: Vec<_> = tx
+                .input
+                .iter()
+                .map(|input| {
+                    block_extra
+                        .outpoint_values()
+                        .get(&input.previous_output)
+                        .expect("prevout not found")
+                        .script_pubkey
+                        .as_script()
+                })
+                .collect();
+            let fingerprint = fingerprint(
+                tx,
+                &prevouts,
+                block_extra.height(),
+                block_extra.block().header.time,
+            );
+            *self.period_fingerprints.entry(fingerprint).or_insert(0) += 1;
+        }
+    }
+
+    /// Keep the most common fingerprints of the current period
+    fn finish_period(&mut self) {
+        let mut fingerprints: Vec<_> = self.period_fingerprints.drain().collect();
+        let txs = fingerprints.iter().map(|(_, count)| count).sum();
+        fingerprints.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        fingerprints.truncate(TOP_FINGERPRINTS);
+        let top_fingerprints = &mut self.stats.top_fingerprints;
+        if top_fingerprints.len() <= self.period_index {
+            top_fingerprints.resize(self.period_index + 1, PeriodFingerprints::default());
+        }
+        top_fingerprints[self.period_index] = PeriodFingerprints {
+            txs,
+            top: fingerprints,
+        };
     }
 
-    fn process_tx(&mut self, txid: Txid, tx: &Transaction, index: usize, block_height: u32) {
+    fn process_v3(&mut self, block_extra: &BlockExtra, index: usize) {
+        let v3: Vec<_> = block_extra
+            .iter_tx()
+            .filter(|(_, tx)| tx.version.0 == 3)
+            .collect();
+        if v3.is_empty() {
+            return;
+        }
+        let v3_txids: HashSet<_> = v3.iter().map(|(txid, _)| **txid).collect();
+        let mut children = HashSet::new();
+        let mut parents = HashSet::new();
+        for (txid, tx) in v3.iter() {
+            for input in tx.input.iter() {
+                if v3_txids.contains(&input.previous_output.txid) {
+                    children.insert(**txid);
+                    parents.insert(input.previous_output.txid);
+                }
+            }
+        }
+        for (txid, tx) in v3.iter() {
+            let structure = match (parents.contains(*txid), children.contains(*txid)) {
+                (false, false) => "standalone",
+                (true, false) => "parent",
+                (false, true) => "child",
+                (true, true) => "parent and child",
+            };
+            self.stats.v3_structure.increment(structure, index);
+            let vsize = match tx.weight().to_vbytes_ceil() {
+                0..=199 => "<200",
+                200..=999 => "200-1000",
+                1_000..=9_999 => "1000-10000",
+                _ => "10000+",
+            };
+            *self.stats.v3_vsize.entry(vsize.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    fn process_tx(
+        &mut self,
+        txid: Txid,
+        tx: &Transaction,
+        index: usize,
+        block_height: u32,
+        block_time: u32,
+    ) {
         let weight = u64::from(tx.weight());
         let outputs = tx.output.len() as u64;
         let inputs = tx.input.len() as u64;
@@ -165,6 +606,55 @@ impl ProcessTxStats {
         };
 
         *self.stats.in_out.entry(in_out_key).or_insert(0) += 1;
+
+        let values: Vec<_> = tx
+            .output
+            .iter()
+            .filter(|o| !o.script_pubkey.is_op_return())
+            .map(|o| o.value.to_sat())
+            .collect();
+        let coinjoin = coinjoin_kind(tx.input.len(), &values);
+        if let Some((kind, value, equal)) = coinjoin {
+            self.stats.coinjoin_per_period.increment(kind, index);
+            self.stats
+                .coinjoin_volume
+                .add(kind, index, value * equal as u64);
+            self.stats
+                .coinjoin_participants
+                .add(kind, index, equal as u64);
+        }
+
+        let vsize = weight.div_ceil(4);
+        self.stats.vsize_per_period.add(index, vsize);
+        if !tx.is_coinbase() {
+            let class = match coinjoin {
+                Some(_) => "coinjoin",
+                None => payment_class(tx.input.len(), values.len()),
+            };
+            self.stats.payment_class_per_period.increment(class, index);
+            if coinjoin.is_none() && values.len() >= 2 {
+                // paying a round amount the change is the only output which isn't round
+                self.stats.change_candidates_per_period.increment(index);
+                let not_round = values.iter().filter(|v| roundness(**v).is_none()).count();
+                // added also when zero, so that it has the length of the candidates
+                self.stats
+                    .round_change_per_period
+                    .add(index, (not_round == 1) as u64);
+            }
+            let payments = payments(values.len()) as u64;
+            self.stats.payments_per_period.add(index, payments);
+            if class == "batched payment" || class == "fan-out" {
+                // every separate payment would need an input, a change output and the overhead
+                let outputs_vbytes: u64 = tx.output.iter().map(|o| o.size() as u64).sum();
+                let inputs_vbytes = vsize.saturating_sub(outputs_vbytes + TX_OVERHEAD_VBYTES);
+                let separate_tx = TX_OVERHEAD_VBYTES
+                    + inputs_vbytes / tx.input.len() as u64
+                    + outputs_vbytes / tx.output.len() as u64;
+                self.stats
+                    .batching_saved_vbytes
+                    .add(index, (payments - 1) * separate_tx);
+            }
+        }
         self.stats.amount_over_32 += tx
             .output
             .iter()
@@ -187,6 +677,9 @@ impl ProcessTxStats {
                 self.stats.rounded_amount_per_period.increment(index);
                 self.stats.rounded_amount += 1;
             }
+            if let Some(level) = roundness(output.value.to_sat()) {
+                self.stats.roundness_per_period.increment(level, index);
+            }
 
             self.stats
                 .script_pubkey_size_per_period
@@ -204,6 +697,35 @@ impl ProcessTxStats {
         if tx.lock_time.to_consensus_u32() > block_height.saturating_sub(6) {
             self.stats.non_deeply_reorgable.increment(index);
         }
+
+        if !tx.is_coinbase() {
+            self.stats.non_coinbase_tx_per_period.increment(index);
+            // the coinbase version is chosen by miners, not by wallets
+            let version = match tx.version.0 {
+                1 => "1",
+                2 => "2",
+                3 => "3",
+                _ => "non-standard",
+            };
+            self.stats.version_per_period.increment(version, index);
+            let locktime =
+                locktime_class(tx.lock_time.to_consensus_u32(), block_height, block_time);
+            self.stats.locktime_per_period.increment(locktime, index);
+            let classes: Vec<_> = tx
+                .input
+                .iter()
+                .map(|i| sequence_class(i.sequence, tx.version.0))
+                .collect();
+            for class in classes.iter() {
+                self.stats.sequence_per_period.increment(class, index);
+            }
+            if tx.is_explicitly_rbf() {
+                self.stats.rbf_signalling.increment(index);
+            }
+            if classes.iter().any(|c| c.starts_with("relative")) {
+                self.stats.relative_locktime.increment(index);
+            }
+        }
     }
 }
 
@@ -218,3 +740,90 @@ impl TxStats {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{coinjoin_kind, locktime_class, payment_class, roundness, sequence_class};
+    use blocks_iterator::bitcoin::Sequence;
+
+    fn kind(inputs: usize, values: &[u64]) -> Option<&'static str> {
+        coinjoin_kind(inputs, values).map(|(kind, _, _)| kind)
+    }
+
+    #[test]
+    fn test_coinjoin_kind() {
+        assert_eq!(kind(1, &[1_000, 2_000]), None);
+        assert_eq!(kind(2, &[1_000, 1_000, 1_000]), None);
+        assert_eq!(kind(5, &[1_000_000; 5]), Some("whirlpool"));
+        assert_eq!(kind(5, &[1_000_001; 5]), Some("joinmarket"));
+        let jm = [50_000, 50_000, 50_000, 12_345, 23_456, 34_567];
+        assert_eq!(kind(4, &jm), Some("joinmarket"));
+        let mut wasabi = vec![10_000_000; 40];
+        wasabi.extend([123_456, 234_567, 345_678]);
+        assert_eq!(kind(60, &wasabi), Some("wasabi 1"));
+        let mut wasabi2: Vec<_> = (0..60).map(|i| 1 << (10 + i % 8)).collect();
+        wasabi2.extend(0..10);
+        assert_eq!(kind(80, &wasabi2), Some("wasabi 2"));
+        let mut other = vec![10_000; 30];
+        other.extend(0..40);
+        assert_eq!(kind(30, &other), Some("other"));
+    }
+
+    #[test]
+    fn test_payment_class() {
+        assert_eq!(payment_class(1, 1), "simple payment");
+        assert_eq!(payment_class(2, 2), "simple payment");
+        assert_eq!(payment_class(1, 3), "batched payment");
+        assert_eq!(payment_class(3, 1), "consolidation");
+        assert_eq!(payment_class(3, 2), "simple payment");
+        assert_eq!(payment_class(1, 10), "fan-out");
+        assert_eq!(payment_class(2, 10), "batched payment");
+        assert_eq!(payment_class(1, 0), "simple payment");
+    }
+
+    #[test]
+    fn test_roundness() {
+        assert_eq!(roundness(0), None);
+        assert_eq!(roundness(999), None);
+        assert_eq!(roundness(123_456), None);
+        assert_eq!(roundness(1_000), Some("1k sat"));
+        assert_eq!(roundness(25_000), Some("1k sat"));
+        assert_eq!(roundness(50_000), Some("10k sat"));
+        assert_eq!(roundness(300_000), Some("0.001 BTC"));
+        assert_eq!(roundness(1_000_000), Some("0.01 BTC"));
+        assert_eq!(roundness(20_000_000), Some("0.1 BTC"));
+        assert_eq!(roundness(100_000_000), Some("1 BTC"));
+        assert_eq!(roundness(5_000_000_000), Some("1 BTC"));
+    }
+
+    #[test]
+    fn test_locktime_class() {
+        let time = 1_700_000_000;
+        assert_eq!(locktime_class(0, 800_000, time), "zero");
+        assert_eq!(locktime_class(799_999, 800_000, time), "tip height");
+        assert_eq!(
+            locktime_class(799_900, 800_000, time),
+            "tip height - random"
+        );
+        assert_eq!(locktime_class(799_899, 800_000, time), "past height");
+        assert_eq!(locktime_class(800_000, 800_000, time), "future height");
+        assert_eq!(locktime_class(time, 800_000, time), "past timestamp");
+        assert_eq!(locktime_class(time + 1, 800_000, time), "future timestamp");
+        assert_eq!(locktime_class(0x2012_3456, 800_000, time), "lightning");
+    }
+
+    #[test]
+    fn test_sequence_class() {
+        assert_eq!(sequence_class(Sequence(0xffff_ffff), 1), "final");
+        assert_eq!(sequence_class(Sequence(0xffff_fffe), 2), "locktime");
+        assert_eq!(sequence_class(Sequence(0xffff_fffd), 2), "rbf");
+        assert_eq!(sequence_class(Sequence(0x8012_3456), 2), "lightning");
+        assert_eq!(sequence_class(Sequence(0), 1), "rbf");
+        assert_eq!(sequence_class(Sequence(0), 2), "relative height");
+        assert_eq!(sequence_class(Sequence(144), 2), "relative height");
+        assert_eq!(sequence_class(Sequence(0x0040_0010), 2), "relative time");
+        assert_eq!(sequence_class(Sequence(0x8000_0010), 2), "lightning");
+        assert_eq!(sequence_class(Sequence(0x4000_0000), 1), "rbf");
+        assert_eq!(sequence_class(Sequence(0x4000_0000), 2), "relative height");
+    }
+}
@@ -0,0 +1,580 @@
+use crate::counter::{Counter, CounterMap};
+use crate::process::disk_map::DiskMap;
+use crate::process::{block_index, compress_amount, script_type_name};
+use bitcoin::hashes::Hash;
+use blocks_iterator::bitcoin::consensus::Encodable;
+use blocks_iterator::bitcoin::{OutPoint, TxOut, Txid, VarInt};
+use blocks_iterator::log::{info, warn};
+use blocks_iterator::BlockExtra;
+use blocks_iterator::PeriodCounter;
+use chrono::{DateTime, Datelike};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Script types of the UTXO, the position is stored in [`Utxo::script_type`]
+pub const UTXO_SCRIPT_TYPES: [&str; 7] = [
+    "p2pkh",
+    "p2pk",
+    "v0_p2wpkh",
+    "v0_p2wsh",
+    "p2sh",
+    "p2tr",
+    "other",
+];
+
+/// Age buckets in blocks, an output belongs to the first bucket with age lower than the limit
+pub const AGE_BUCKETS: [(u32, &str); 8] = [
+    (144, "<1d"),
+    (1_008, "1d-1w"),
+    (4_320, "1w-1m"),
+    (26_280, "1m-6m"),
+    (52_560, "6m-1y"),
+    (105_120, "1y-2y"),
+    (262_800, "2y-5y"),
+    (u32::MAX, "5y+"),
+];
+
+/// Blocks in a year, used to convert `--dormant-years` in an age
+const BLOCKS_PER_YEAR: u32 = 52_560;
+
+/// Number of the largest movements of dormant coins kept in [`UtxoStats::notable_awakened`]
+const NOTABLE_AWAKENED: usize = 100;
+
+pub fn age_bucket(age: u32) -> &'static str {
+    AGE_BUCKETS
+        .iter()
+        .find(|(limit, _)| age < *limit)
+        .map(|(_, label)| *label)
+        .unwrap_or("5y+")
+}
+
+pub struct ProcessUtxo {
+    receiver: Receiver<Arc<Option<BlockExtra>>>,
+    pub stats: UtxoStats,
+
+    /// the UTXO set keyed by [`utxo_key`], with the values serialized by [`Utxo::to_bytes`]
+    utxo: DiskMap<12, 16>,
+    /// outputs with the same [`utxo_key`] of another unspent output
+    collisions: HashMap<OutPoint, Utxo>,
+    /// value of the UTXO set by creation height, to compute the age distribution
+    unspent_value_by_height: Vec<u64>,
+    /// year of the block timestamp by height, the era of creation of an output
+    years: Vec<u16>,
+    /// outputs spent with an age of at least this number of blocks are awakened
+    dormant_age: u32,
+    utxo_stats_json_file: File,
+    snapshot_path: PathBuf,
+}
+
+/// 16 bytes compact representation of an unspent output
+#[derive(Clone, Copy)]
+pub struct Utxo {
+    pub value: u64,
+    pub height: u32,
+    /// serialized size of the output
+    pub size: u16,
+    /// index in [`UTXO_SCRIPT_TYPES`]
+    pub script_type: u8,
+    /// bitset of [`Utxo::DUST`], [`Utxo::COINBASE`] and [`Utxo::UNCOMPRESSED`]
+    pub flags: u8,
+}
+
+/// Spend of an output older than `--dormant-years`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Awakened {
+    pub height: u32,
+    pub txid: Txid,
+    pub value: u64,
+    pub created_height: u32,
+    pub created_year: u16,
+    pub script_type: String,
+}
+
+/// Outputs created and spent per period, the UTXO set at the end of a period is the cumulative
+/// difference between the two
+#[derive(Default, Serialize, Deserialize)]
+pub struct UtxoStats {
+    pub created_per_type: CounterMap,
+    pub spent_per_type: CounterMap,
+    pub created_value: Counter,
+    pub spent_value: Counter,
+    pub created_size: Counter,
+    pub spent_size: Counter,
+    /// outputs with a value lower than the dust limit of their script
+    pub created_dust: Counter,
+    pub spent_dust: Counter,
+    /// spent outputs not found in the UTXO set
+    pub missing_spent: u64,
+
+    /// number of UTXO at the tip, by amount in satoshi rounded down to a power of 10
+    pub tip_amounts: BTreeMap<String, u64>,
+    pub tip_count: u64,
+    pub tip_value: u64,
+    pub tip_height: u32,
+
+    /// sum of the value of the spent outputs multiplied by their age, in bitcoin days
+    pub coin_days_destroyed: Counter,
+    /// spent outputs by age bucket
+    pub spent_per_age: CounterMap,
+    /// value of the UTXO set by age bucket, at the end of every period
+    pub hodl_waves: CounterMap,
+    /// value of the UTXO set at the tip by age bucket
+    pub tip_ages: BTreeMap<String, u64>,
+
+    /// minimum age in years of the awakened outputs
+    pub dormant_years: u32,
+    /// spent outputs older than `dormant_years`, keyed by `all`, `p2pk` and `uncompressed p2pk`
+    pub awakened: CounterMap,
+    pub awakened_value: Counter,
+    /// awakened outputs by year of creation
+    pub awakened_per_year: CounterMap,
+    /// the awakened outputs with the highest value
+    pub notable_awakened: Vec<Awakened>,
+
+    /// P2PK UTXO at the tip by year of creation
+    pub tip_p2pk_per_year: BTreeMap<String, u64>,
+    pub tip_p2pk_value_per_year: BTreeMap<String, u64>,
+    pub tip_coinbase_p2pk_per_year: BTreeMap<String, u64>,
+    /// P2PK UTXO at the tip locked to an uncompressed public key by year of creation
+    pub tip_uncompressed_per_year: BTreeMap<String, u64>,
+}
+
+impl ProcessUtxo {
+    pub fn new(
+        receiver: Receiver<Arc<Option<BlockExtra>>>,
+        target_dir: &Path,
+        dormant_years: u32,
+        utxo_mb: Option<usize>,
+    ) -> Self {
+        let utxo_stats_json_file =
+            File::create(format!("{}/raw/utxo_stats.json", target_dir.display())).unwrap();
+        let mut snapshot_path = target_dir.to_path_buf();
+        snapshot_path.push("raw");
+        snapshot_path.push("utxo_snapshot.bin");
+        let utxo_path = snapshot_path.with_file_name("utxo_set.bin");
+        ProcessUtxo {
+            receiver,
+            stats: UtxoStats {
+                dormant_years,
+                ..Default::default()
+            },
+            utxo: DiskMap::new(&utxo_path, utxo_mb.map(|mb| mb << 20)),
+            collisions: HashMap::new(),
+            unspent_value_by_height: vec![],
+            years: vec![],
+            dormant_age: dormant_years * BLOCKS_PER_YEAR,
+            utxo_stats_json_file,
+            snapshot_path,
+        }
+    }
+
+    pub fn start(mut self) -> UtxoStats {
+        let mut busy_time = 0u128;
+        let mut now = Instant::now();
+        let mut period = PeriodCounter::new(Duration::from_secs(10));
+        loop {
+            busy_time += now.elapsed().as_nanos();
+            let received = self.receiver.recv().expect("cannot receive fee block");
+            now = Instant::now();
+            match *received {
+                Some(ref block) => {
+                    self.process_block(block);
+                    if period.period_elapsed().is_some() {
+                        info!(
+                            "busy_time:{} utxo:{}",
+                            (busy_time / 1_000_000_000),
+                            self.utxo.len()
+                        );
+                    }
+                }
+                None => break,
+            }
+        }
+
+        self.finish_tip();
+        self.write_snapshot();
+        sort_notable(&mut self.stats.notable_awakened);
+
+        let utxo_stats_json = serde_json::to_string(&self.stats).unwrap();
+        self.utxo_stats_json_file
+            .write_all(utxo_stats_json.as_bytes())
+            .unwrap();
+
+        busy_time += now.elapsed().as_nanos();
+        info!(
+            "ending utxo processer, busy time: {}s",
+            (busy_time / 1_000_000_000)
+        );
+
+        self.stats
+    }
+
+    fn process_block(&mut self, block_extra: &BlockExtra) {
+        let height = block_extra.height();
+        let index = block_index(height);
+        self.stats.tip_height = height;
+        self.unspent_value_by_height.resize(height as usize + 1, 0);
+        let year = DateTime::from_timestamp(block_extra.block().header.time as i64, 0)
+            .map(|t| t.year() as u16)
+            .unwrap_or(0);
+        self.years.resize(height as usize + 1, year);
+        let mut satoshi_blocks_destroyed = 0u128;
+
+        // every block adds to the created and spent counters, so that their periods are aligned
+        for script_type in UTXO_SCRIPT_TYPES.iter() {
+            self.stats.created_per_type.add(script_type, index, 0);
+            self.stats.spent_per_type.add(script_type, index, 0);
+        }
+        for counter in [
+            &mut self.stats.created_value,
+            &mut self.stats.spent_value,
+            &mut self.stats.created_size,
+            &mut self.stats.spent_size,
+            &mut self.stats.created_dust,
+            &mut self.stats.spent_dust,
+        ] {
+            counter.add(index, 0);
+        }
+
+        for (txid, tx) in block_extra.iter_tx() {
+            if !tx.is_coinbase() {
+                for input in tx.input.iter() {
+                    let utxo = match self.remove_utxo(&input.previous_output) {
+                        Some(utxo) => utxo,
+                        None => {
+                            warn!(
+                                "{} spends {} not in the utxo set",
+                                txid, input.previous_output
+                            );
+                            self.stats.missing_spent += 1;
+                            continue;
+                        }
+                    };
+                    self.remove_from_stats(&utxo, index);
+                    let age = height - utxo.height;
+                    if age >= self.dormant_age {
+                        self.awaken(txid, &utxo, height);
+                    }
+                    self.stats.spent_per_age.increment(age_bucket(age), index);
+                    satoshi_blocks_destroyed += utxo.value as u128 * age as u128;
+                }
+            }
+            for (vout, output) in tx.output.iter().enumerate() {
+                if output.script_pubkey.is_op_return() {
+                    continue;
+                }
+                let utxo = Utxo::new(output, height, tx.is_coinbase());
+                self.insert_utxo(OutPoint::new(*txid, vout as u32), utxo, tx.is_coinbase());
+            }
+        }
+
+        // a day is 144 blocks and a bitcoin is 100_000_000 satoshi
+        self.stats.coin_days_destroyed.add(
+            index,
+            (satoshi_blocks_destroyed / (144 * 100_000_000)) as u64,
+        );
+
+        if block_index(height + 1) != index {
+            for (bucket, value) in self.value_per_age(height) {
+                self.stats.hodl_waves.add(bucket, index, value);
+            }
+        }
+    }
+
+    /// Insert an output created in the current block and add it to the stats
+    fn insert_utxo(&mut self, outpoint: OutPoint, utxo: Utxo, coinbase: bool) {
+        let index = block_index(utxo.height);
+        self.unspent_value_by_height[utxo.height as usize] += utxo.value;
+        let script_type = UTXO_SCRIPT_TYPES[utxo.script_type as usize];
+        self.stats.created_per_type.increment(script_type, index);
+        self.stats.created_value.add(index, utxo.value);
+        self.stats.created_size.add(index, utxo.size as u64);
+        if utxo.is_dust() {
+            self.stats.created_dust.increment(index);
+        }
+
+        let key = utxo_key(&outpoint);
+        if let Some(previous) = self.utxo.get(&key) {
+            if !coinbase {
+                self.collisions.insert(outpoint, utxo);
+                return;
+            }
+            // duplicated coinbase txids (BIP30) overwrite the previous output like in core, the
+            // overwritten one is lost and leaves the UTXO set like a spent output
+            self.utxo.remove(&key);
+            self.remove_from_stats(&Utxo::from_bytes(previous), index);
+        }
+        self.utxo.insert(key, utxo.to_bytes());
+    }
+
+    /// Remove an output leaving the UTXO set in the period `index` from the stats
+    fn remove_from_stats(&mut self, utxo: &Utxo, index: usize) {
+        let script_type = UTXO_SCRIPT_TYPES[utxo.script_type as usize];
+        self.stats.spent_per_type.increment(script_type, index);
+        self.stats.spent_value.add(index, utxo.value);
+        self.stats.spent_size.add(index, utxo.size as u64);
+        if utxo.is_dust() {
+            self.stats.spent_dust.increment(index);
+        }
+        self.unspent_value_by_height[utxo.height as usize] -= utxo.value;
+    }
+
+    fn remove_utxo(&mut self, outpoint: &OutPoint) -> Option<Utxo> {
+        if let Some(utxo) = self.collisions.remove(outpoint) {
+            return Some(utxo);
+        }
+        self.utxo.remove(&utxo_key(outpoint)).map(Utxo::from_bytes)
+    }
+
+    fn awaken(&mut self, txid: &Txid, utxo: &Utxo, height: u32) {
+        let index = block_index(height);
+        let created_year = self.years[utxo.height as usize];
+        let script_type = UTXO_SCRIPT_TYPES[utxo.script_type as usize];
+        self.stats.awakened.increment("all", index);
+        self.stats.awakened_value.add(index, utxo.value);
+        self.stats
+            .awakened_per_year
+            .increment(&created_year.to_string(), index);
+        if script_type == "p2pk" {
+            self.stats.awakened.increment("p2pk", index);
+        }
+        if utxo.is_uncompressed() {
+            self.stats.awakened.increment("uncompressed p2pk", index);
+        }
+        let notable = &mut self.stats.notable_awakened;
+        notable.push(Awakened {
+            height,
+            txid: *txid,
+            value: utxo.value,
+            created_height: utxo.height,
+            created_year,
+            script_type: script_type.to_string(),
+        });
+        if notable.len() > 2 * NOTABLE_AWAKENED {
+            sort_notable(notable);
+        }
+    }
+
+    /// value of the UTXO set at `height` by age bucket
+    fn value_per_age(&self, height: u32) -> Vec<(&'static str, u64)> {
+        let mut result = vec![];
+        let mut newer = height as usize + 1;
+        for (limit, label) in AGE_BUCKETS.iter() {
+            let older = (height as usize + 1).saturating_sub(*limit as usize);
+            let value = self.unspent_value_by_height[older..newer].iter().sum();
+            result.push((*label, value));
+            newer = older;
+        }
+        result
+    }
+
+    fn finish_tip(&mut self) {
+        let stats = &mut self.stats;
+        let years = &self.years;
+        self.utxo
+            .for_each(|_, utxo| add_tip_utxo(stats, years, &Utxo::from_bytes(*utxo)));
+        for utxo in self.collisions.values() {
+            add_tip_utxo(stats, years, utxo);
+        }
+        self.stats.tip_count = (self.utxo.len() + self.collisions.len()) as u64;
+        if self.unspent_value_by_height.is_empty() {
+            return;
+        }
+        let tip_index = block_index(self.stats.tip_height);
+        for (bucket, value) in self.value_per_age(self.stats.tip_height) {
+            self.stats.tip_ages.insert(bucket.to_string(), value);
+            if block_index(self.stats.tip_height + 1) == tip_index {
+                // the incomplete last period, so that it's the one skipped by `finish`
+                self.stats.hodl_waves.add(bucket, tip_index, value);
+            }
+        }
+    }
+
+    /// Write the UTXO set at the tip, every element is: the 12 bytes [`utxo_key`] of the
+    /// outpoint, the creation height as 4 bytes, the script type as 1 byte and the compressed
+    /// amount as varint.
+    fn write_snapshot(&mut self) {
+        let mut file = BufWriter::new(File::create(&self.snapshot_path).unwrap());
+        file.write_all(&self.stats.tip_height.to_be_bytes())
+            .unwrap();
+        let mut write = |key: &[u8; 12], utxo: &Utxo| {
+            file.write_all(key).unwrap();
+            file.write_all(&utxo.height.to_be_bytes()).unwrap();
+            file.write_all(&[utxo.script_type]).unwrap();
+            VarInt(compress_amount(utxo.value))
+                .consensus_encode(&mut file)
+                .unwrap();
+        };
+        self.utxo
+            .for_each(|key, utxo| write(key, &Utxo::from_bytes(*utxo)));
+        for (outpoint, utxo) in self.collisions.iter() {
+            write(&utxo_key(outpoint), utxo);
+        }
+    }
+}
+
+impl Utxo {
+    /// value lower than the dust limit of the script
+    const DUST: u8 = 1;
+    /// created in a coinbase transaction
+    const COINBASE: u8 = 2;
+    /// P2PK locked to an uncompressed public key
+    const UNCOMPRESSED: u8 = 4;
+
+    fn new(output: &TxOut, height: u32, coinbase: bool) -> Self {
+        let script_type = script_type_name(&output.script_pubkey);
+        let script_type = UTXO_SCRIPT_TYPES
+            .iter()
+            .position(|t| *t == script_type)
+            .unwrap_or(UTXO_SCRIPT_TYPES.len() - 1);
+        let mut flags = 0;
+        if output.value < output.script_pubkey.minimal_non_dust() {
+            flags |= Utxo::DUST;
+        }
+        if coinbase {
+            flags |= Utxo::COINBASE;
+        }
+        // 65 bytes public key push and OP_CHECKSIG
+        if output.script_pubkey.is_p2pk() && output.script_pubkey.len() == 67 {
+            flags |= Utxo::UNCOMPRESSED;
+        }
+        Utxo {
+            value: output.value.to_sat(),
+            height,
+            size: output.size().min(u16::MAX as usize) as u16,
+            script_type: script_type as u8,
+            flags,
+        }
+    }
+
+    fn to_bytes(self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.value.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.height.to_be_bytes());
+        bytes[12..14].copy_from_slice(&self.size.to_be_bytes());
+        bytes[14] = self.script_type;
+        bytes[15] = self.flags;
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; 16]) -> Self {
+        Utxo {
+            value: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            height: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            size: u16::from_be_bytes(bytes[12..14].try_into().unwrap()),
+            script_type: bytes[14],
+            flags: bytes[15],
+        }
+    }
+
+    pub fn is_dust(&self) -> bool {
+        self.flags & Utxo::DUST != 0
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.flags & Utxo::COINBASE != 0
+    }
+
+    pub fn is_uncompressed(&self) -> bool {
+        self.flags & Utxo::UNCOMPRESSED != 0
+    }
+}
+
+/// Add an output of the UTXO set at the tip to the stats
+fn add_tip_utxo(stats: &mut UtxoStats, years: &[u16], utxo: &Utxo) {
+    let bucket = if utxo.value == 0 {
+        0
+    } else {
+        10u64.pow((utxo.value as f64).log10() as u32)
+    };
+    *stats
+        .tip_amounts
+        .entry(format!("{:>16}", bucket))
+        .or_insert(0) += 1;
+    stats.tip_value += utxo.value;
+
+    if UTXO_SCRIPT_TYPES[utxo.script_type as usize] == "p2pk" {
+        let year = years[utxo.height as usize].to_string();
+        *stats.tip_p2pk_per_year.entry(year.clone()).or_insert(0) += 1;
+        *stats
+            .tip_p2pk_value_per_year
+            .entry(year.clone())
+            .or_insert(0) += utxo.value;
+        if utxo.is_coinbase() {
+            *stats
+                .tip_coinbase_p2pk_per_year
+                .entry(year.clone())
+                .or_insert(0) += 1;
+        }
+        if utxo.is_uncompressed() {
+            *stats.tip_uncompressed_per_year.entry(year).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Keep only the [`NOTABLE_AWAKENED`] awakened outputs with the highest value, older first when
+/// the value is the same
+fn sort_notable(notable: &mut Vec<Awakened>) {
+    notable.sort_by(|a, b| {
+        b.value
+            .cmp(&a.value)
+            .then(a.created_height.cmp(&b.created_height))
+    });
+    notable.truncate(NOTABLE_AWAKENED);
+}
+
+/// Key of the outpoint in the UTXO set: the first 8 bytes of the txid and the vout as 4 bytes big
+/// endian. Outputs of different txids with the same key are kept in [`ProcessUtxo::collisions`]
+fn utxo_key(outpoint: &OutPoint) -> [u8; 12] {
+    let mut key = [0u8; 12];
+    key[..8].copy_from_slice(&outpoint.txid.as_raw_hash().as_byte_array()[..8]);
+    key[8..].copy_from_slice(&outpoint.vout.to_be_bytes());
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ProcessUtxo, Utxo};
+    use blocks_iterator::bitcoin::hashes::Hash;
+    use blocks_iterator::bitcoin::{Amount, OutPoint, ScriptBuf, TxOut, Txid};
+
+    #[test]
+    fn test_duplicated_coinbase() {
+        let dir = std::env::temp_dir().join("test_duplicated_coinbase");
+        std::fs::create_dir_all(dir.join("raw")).unwrap();
+        let (_sender, receiver) = std::sync::mpsc::channel();
+        let mut process = ProcessUtxo::new(receiver, &dir, 1, None);
+        process.unspent_value_by_height.resize(3, 0);
+
+        let output = TxOut {
+            value: Amount::from_sat(50 * 100_000_000),
+            script_pubkey: ScriptBuf::new_p2pkh(&Hash::all_zeros()),
+        };
+        let outpoint = OutPoint::new(Txid::all_zeros(), 0);
+        process.insert_utxo(outpoint, Utxo::new(&output, 1, true), true);
+        process.insert_utxo(outpoint, Utxo::new(&output, 2, true), true);
+
+        assert_eq!(process.utxo.len(), 1);
+        assert_eq!(
+            process.unspent_value_by_height,
+            vec![0, 0, 50 * 100_000_000]
+        );
+        let stats = &process.stats;
+        let value = stats.created_value.sum_all() - stats.spent_value.sum_all();
+        assert_eq!(value, 50 * 100_000_000);
+        let count =
+            stats.created_per_type.sum_all()["p2pkh"] - stats.spent_per_type.sum_all()["p2pkh"];
+        assert_eq!(count, 1);
+        assert_eq!(
+            stats.created_size.sum_all() - stats.spent_size.sum_all(),
+            output.size() as u64
+        );
+    }
+}
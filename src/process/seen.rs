@@ -0,0 +1,91 @@
+use crate::process::disk_map::DiskMap;
+use bitcoin::hashes::siphash24;
+use std::path::Path;
+
+/// Set of the elements seen so far, every element is stored as a 64 bits hash with a value of
+/// `N` bytes. Differently from a bloom filter the set is exact: the chance of two different
+/// elements having the same hash is about 1 in 20 billion per lookup with a billion elements.
+/// Elements not fitting in memory are kept on disk, see [`DiskMap`].
+pub struct SeenIndex<const N: usize> {
+    map: DiskMap<8, N>,
+}
+
+/// A [`SeenIndex`] without values
+pub type SeenSet = SeenIndex<0>;
+
+impl<const N: usize> SeenIndex<N> {
+    /// Uses about `bytes` of memory, the elements not fitting are written in `path`
+    pub fn new(path: &Path, bytes: usize) -> Self {
+        Self {
+            map: DiskMap::new(path, Some(bytes)),
+        }
+    }
+
+    /// Insert the element with the given value if it wasn't seen, otherwise return the value
+    /// inserted with the element the first time
+    pub fn insert(&mut self, element: &[u8], value: [u8; N]) -> Option<[u8; N]> {
+        let key = hash(element);
+        if let Some(previous) = self.map.get(&key) {
+            return Some(previous);
+        }
+        self.map.insert(key, value);
+        None
+    }
+
+    /// The value of the element if seen
+    pub fn get(&mut self, element: &[u8]) -> Option<[u8; N]> {
+        self.map.get(&hash(element))
+    }
+
+    /// Insert the element or replace its value
+    pub fn set(&mut self, element: &[u8], value: [u8; N]) {
+        let key = hash(element);
+        self.map.remove(&key);
+        self.map.insert(key, value);
+    }
+
+    /// Number of elements seen
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+/// 64 bits hash of the element, stable across executions and compiler versions
+fn hash(element: &[u8]) -> [u8; 8] {
+    siphash24::Hash::hash_to_u64_with_keys(0, 0, element).to_be_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SeenIndex, SeenSet};
+
+    #[test]
+    fn test_seen_index() {
+        let dir = std::env::temp_dir();
+        // small enough to merge on disk many times
+        let mut set = SeenSet::new(&dir.join("test_seen_set.bin"), 1024);
+        for i in 0u32..10_000 {
+            assert!(set.insert(&i.to_be_bytes(), []).is_none());
+        }
+        for i in 0u32..10_000 {
+            assert!(set.insert(&i.to_be_bytes(), []).is_some());
+        }
+        assert_eq!(set.len(), 10_000);
+        assert!(set.insert(&10_000u32.to_be_bytes(), []).is_none());
+
+        let mut map = SeenIndex::<4>::new(&dir.join("test_seen_index.bin"), 1024);
+        for i in 0u32..5_000 {
+            assert_eq!(map.insert(&i.to_le_bytes(), (i * 2).to_be_bytes()), None);
+        }
+        for i in 0u32..5_000 {
+            let value = map.insert(&i.to_le_bytes(), [0; 4]);
+            assert_eq!(value, Some((i * 2).to_be_bytes()));
+            map.set(&i.to_le_bytes(), i.to_be_bytes());
+        }
+        for i in 0u32..5_000 {
+            assert_eq!(map.get(&i.to_le_bytes()), Some(i.to_be_bytes()));
+        }
+        assert_eq!(map.get(&5_000u32.to_le_bytes()), None);
+        assert_eq!(map.len(), 5_000);
+    }
+}